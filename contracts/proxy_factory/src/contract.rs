@@ -0,0 +1,300 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    attr, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response, StdResult,
+    SubMsg, WasmMsg,
+};
+use cw_storage_plus::Bound;
+use cw_utils::parse_reply_instantiate_data;
+
+use crate::error::ContractError;
+use crate::state::{Config, CONFIG, PENDING_PROXY, PROXIES};
+use ap_valkyrie::factory::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, ProxiesResponse, ProxyInfo, QueryMsg,
+};
+use ap_valkyrie::lp_staking;
+use cw2::set_contract_version;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "astroport-generator-proxy-factory";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Reply of the submessage instantiating a proxy
+pub(crate) const INSTANTIATE_PROXY_REPLY_ID: u64 = 1;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+    let proxy_admin = msg
+        .proxy_admin
+        .map(|admin| deps.api.addr_validate(&admin))
+        .transpose()?;
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            owner,
+            proxy_code_id: msg.proxy_code_id,
+            generator_contract_addr: deps.api.addr_validate(&msg.generator_contract_addr)?,
+            proxy_admin,
+        },
+    )?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::CreateProxy {
+            pair_addr,
+            lp_token_addr,
+            reward_contract_addr,
+            reward_token_addr,
+            label,
+            salt,
+        } => create_proxy(
+            deps,
+            info,
+            pair_addr,
+            lp_token_addr,
+            reward_contract_addr,
+            reward_token_addr,
+            label,
+            salt,
+        ),
+        ExecuteMsg::UpdateConfig {
+            proxy_code_id,
+            proxy_admin,
+        } => update_config(deps, info, proxy_code_id, proxy_admin),
+    }
+}
+
+/// @dev Instantiates a proxy for the LP token, the proxy is registered once the instantiation replies.
+/// The pair, LP token and reward token must match the config of the VKR LP staking contract
+/// @param pair_addr : The Astroport pair contract address
+/// @param lp_token_addr : The LP token contract address
+/// @param reward_contract_addr : The VKR LP staking contract address
+/// @param reward_token_addr : The reward token contract address
+/// @param label : The proxy label
+/// @param salt : Instantiates the proxy with instantiate2 at the address derived from the salt
+#[allow(clippy::too_many_arguments)]
+fn create_proxy(
+    deps: DepsMut,
+    info: MessageInfo,
+    pair_addr: String,
+    lp_token_addr: String,
+    reward_contract_addr: String,
+    reward_token_addr: String,
+    label: Option<String>,
+    salt: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair = deps.api.addr_validate(&pair_addr)?;
+    let lp_token = deps.api.addr_validate(&lp_token_addr)?;
+    let reward_contract = deps.api.addr_validate(&reward_contract_addr)?;
+    let reward_token = deps.api.addr_validate(&reward_token_addr)?;
+
+    if PROXIES.has(deps.storage, &lp_token) {
+        return Err(ContractError::ProxyAlreadyExists {
+            lp_token: lp_token.to_string(),
+        });
+    }
+
+    let upstream = lp_staking::query_config(&deps.querier, &reward_contract)?;
+    for (field, expected, actual) in [
+        ("Pair", &pair, upstream.pair),
+        ("LP token", &lp_token, upstream.lp_token),
+        ("Reward token", &reward_token, upstream.token),
+    ] {
+        if expected.as_str() != actual {
+            return Err(ContractError::StakingConfigMismatch {
+                field: field.to_string(),
+                expected: expected.to_string(),
+                actual,
+            });
+        }
+    }
+
+    PENDING_PROXY.save(deps.storage, &lp_token)?;
+
+    let admin = cfg.proxy_admin.map(|admin| admin.to_string());
+    let msg = to_binary(&ap_valkyrie::InstantiateMsg {
+        generator_contract_addr: cfg.generator_contract_addr.to_string(),
+        pair_addr: pair.to_string(),
+        lp_token_addr: lp_token.to_string(),
+        reward_contract_addr: reward_contract.to_string(),
+        reward_token_addr: reward_token.to_string(),
+        owner: Some(cfg.owner.to_string()),
+    })?;
+    let label =
+        label.unwrap_or_else(|| format!("Astroport generator proxy to VKR for {}", lp_token));
+    let instantiate_msg = match salt {
+        #[cfg(feature = "cosmwasm_1_2")]
+        Some(salt) => {
+            if salt.is_empty() || salt.len() > 64 {
+                return Err(ContractError::InvalidSalt {});
+            }
+            WasmMsg::Instantiate2 {
+                admin,
+                code_id: cfg.proxy_code_id,
+                label,
+                msg,
+                funds: vec![],
+                salt,
+            }
+        }
+        #[cfg(not(feature = "cosmwasm_1_2"))]
+        Some(_) => return Err(ContractError::Instantiate2Unsupported {}),
+        None => WasmMsg::Instantiate {
+            admin,
+            code_id: cfg.proxy_code_id,
+            msg,
+            funds: vec![],
+            label,
+        },
+    };
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            instantiate_msg,
+            INSTANTIATE_PROXY_REPLY_ID,
+        ))
+        .add_attributes(vec![
+            attr("action", "create_proxy"),
+            attr("lp_token", lp_token),
+        ]))
+}
+
+/// @dev Updates the factory config. Can only be called by the owner
+/// @param proxy_code_id : The code id used for new proxies
+/// @param proxy_admin : The admin of new proxies
+fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    proxy_code_id: Option<u64>,
+    proxy_admin: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(proxy_code_id) = proxy_code_id {
+        cfg.proxy_code_id = proxy_code_id;
+    }
+    if let Some(proxy_admin) = proxy_admin {
+        cfg.proxy_admin = Some(deps.api.addr_validate(&proxy_admin)?);
+    }
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_PROXY_REPLY_ID => register_proxy(deps, msg),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+/// @dev Registers the instantiated proxy for the LP token waiting for the reply
+/// @param msg : The reply of the instantiate submessage
+fn register_proxy(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let lp_token = PENDING_PROXY.load(deps.storage)?;
+    PENDING_PROXY.remove(deps.storage);
+
+    let res =
+        parse_reply_instantiate_data(msg).map_err(|_| ContractError::MissingProxyAddress {})?;
+    let proxy = deps.api.addr_validate(&res.contract_address)?;
+
+    PROXIES.save(deps.storage, &lp_token, &proxy)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_proxy"),
+        attr("lp_token", lp_token),
+        attr("proxy", proxy),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => {
+            let cfg = CONFIG.load(deps.storage)?;
+            to_binary(&ConfigResponse {
+                owner: cfg.owner,
+                proxy_code_id: cfg.proxy_code_id,
+                generator_contract_addr: cfg.generator_contract_addr,
+                proxy_admin: cfg.proxy_admin,
+            })
+        }
+        QueryMsg::Proxies { start_after, limit } => {
+            to_binary(&query_proxies(deps, start_after, limit)?)
+        }
+        QueryMsg::ProxyFor { lp_token } => {
+            let lp_token = deps.api.addr_validate(&lp_token)?;
+            let proxy = PROXIES.load(deps.storage, &lp_token)?;
+            to_binary(&ProxyInfo { lp_token, proxy })
+        }
+    }
+}
+
+/// @dev Returns the registered proxies ordered by LP token address
+/// @param start_after : The LP token address to start after
+/// @param limit : Maximum number of proxies returned
+fn query_proxies(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ProxiesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|lp_token| deps.api.addr_validate(&lp_token))
+        .transpose()?;
+
+    let proxies = PROXIES
+        .range(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (lp_token, proxy) = item?;
+            Ok(ProxyInfo { lp_token, proxy })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ProxiesResponse { proxies })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::default())
+}
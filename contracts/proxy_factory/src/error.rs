@@ -0,0 +1,33 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("A proxy is already registered for {lp_token}")]
+    ProxyAlreadyExists { lp_token: String },
+
+    #[error("{field} mismatch: expected {expected}, staking contract uses {actual}")]
+    StakingConfigMismatch {
+        field: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("The instantiate reply does not contain the proxy address")]
+    MissingProxyAddress {},
+
+    #[error("The factory is built without instantiate2 support")]
+    Instantiate2Unsupported {},
+
+    #[error("The instantiate2 salt must be between 1 and 64 bytes")]
+    InvalidSalt {},
+}
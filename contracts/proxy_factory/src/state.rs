@@ -0,0 +1,20 @@
+use cosmwasm_schema::cw_serde;
+
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+
+#[cw_serde]
+pub struct Config {
+    pub owner: Addr,
+    pub proxy_code_id: u64,
+    pub generator_contract_addr: Addr,
+    pub proxy_admin: Option<Addr>,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// The proxy instantiated for every LP token
+pub const PROXIES: Map<&Addr, Addr> = Map::new("proxies");
+
+/// LP token of the proxy waiting for the reply of the instantiate submessage
+pub const PENDING_PROXY: Item<Addr> = Item::new("pending_proxy");
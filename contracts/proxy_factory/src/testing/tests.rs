@@ -0,0 +1,310 @@
+use crate::contract::{execute, instantiate, query, reply, INSTANTIATE_PROXY_REPLY_ID};
+use crate::error::ContractError;
+use ap_valkyrie::factory::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, ProxiesResponse, ProxyInfo, QueryMsg,
+};
+use ap_valkyrie_testing::{
+    mock_dependencies, ProxyInstantiateMsgBuilder, StakingConfig, WasmMockQuerier, GENERATOR,
+    LP_TOKEN, PAIR, REWARD_CONTRACT, REWARD_TOKEN,
+};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, Event, OwnedDeps, Reply, SubMsg, SubMsgResponse,
+    SubMsgResult, WasmMsg,
+};
+
+const OWNER: &str = "owner0000";
+const PROXY_CODE_ID: u64 = 7;
+
+type FactoryDeps = OwnedDeps<MockStorage, MockApi, WasmMockQuerier>;
+
+fn setup() -> FactoryDeps {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        proxy_code_id: PROXY_CODE_ID,
+        generator_contract_addr: GENERATOR.to_string(),
+        proxy_admin: Some("admin0000".to_string()),
+        owner: Some(OWNER.to_string()),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    deps
+}
+
+fn create_proxy_msg(lp_token: &str, reward_contract: &str) -> ExecuteMsg {
+    ExecuteMsg::CreateProxy {
+        pair_addr: PAIR.to_string(),
+        lp_token_addr: lp_token.to_string(),
+        reward_contract_addr: reward_contract.to_string(),
+        reward_token_addr: REWARD_TOKEN.to_string(),
+        label: None,
+        salt: None,
+    }
+}
+
+/// Reply of the proxy instantiation, its data is the protobuf encoded
+/// `MsgInstantiateContractResponse` holding the proxy address
+fn instantiate_reply(proxy: &str) -> Reply {
+    let mut data = vec![0x0a, proxy.len() as u8];
+    data.extend_from_slice(proxy.as_bytes());
+    Reply {
+        id: INSTANTIATE_PROXY_REPLY_ID,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![Event::new("instantiate")
+                .add_attribute("_contract_address", proxy)
+                .add_attribute("code_id", PROXY_CODE_ID.to_string())],
+            data: Some(Binary::from(data)),
+        }),
+    }
+}
+
+#[test]
+fn test_proper_initialization() {
+    let deps = setup();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    assert_eq!(
+        from_binary::<ConfigResponse>(&res).unwrap(),
+        ConfigResponse {
+            owner: Addr::unchecked(OWNER),
+            proxy_code_id: PROXY_CODE_ID,
+            generator_contract_addr: Addr::unchecked(GENERATOR),
+            proxy_admin: Some(Addr::unchecked("admin0000")),
+        }
+    );
+}
+
+#[test]
+fn test_create_proxy() {
+    let mut deps = setup();
+    deps.querier.ledger_mut().set_staking_config(
+        "reward0001",
+        StakingConfig {
+            token: REWARD_TOKEN.to_string(),
+            pair: PAIR.to_string(),
+            lp_token: "otherlp0000".to_string(),
+        },
+    );
+
+    // only the owner can create proxies
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        create_proxy_msg(LP_TOKEN, REWARD_CONTRACT),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the parameters must match the staking contract config
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        create_proxy_msg(LP_TOKEN, "reward0001"),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::StakingConfigMismatch {
+            field: "LP token".to_string(),
+            expected: LP_TOKEN.to_string(),
+            actual: "otherlp0000".to_string(),
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        create_proxy_msg(LP_TOKEN, REWARD_CONTRACT),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Instantiate {
+                admin: Some("admin0000".to_string()),
+                code_id: PROXY_CODE_ID,
+                msg: to_binary(&ProxyInstantiateMsgBuilder::new().owner(OWNER).build()).unwrap(),
+                funds: vec![],
+                label: format!("Astroport generator proxy to VKR for {}", LP_TOKEN),
+            },
+            INSTANTIATE_PROXY_REPLY_ID
+        )]
+    );
+
+    // the proxy is registered once instantiated
+    reply(deps.as_mut(), mock_env(), instantiate_reply("proxy0000")).unwrap();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ProxyFor {
+            lp_token: LP_TOKEN.to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<ProxyInfo>(&res).unwrap(),
+        ProxyInfo {
+            lp_token: Addr::unchecked(LP_TOKEN),
+            proxy: Addr::unchecked("proxy0000"),
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        create_proxy_msg(LP_TOKEN, REWARD_CONTRACT),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ProxyAlreadyExists {
+            lp_token: LP_TOKEN.to_string(),
+        }
+    );
+
+    // unknown replies are rejected
+    let err = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 42,
+            ..instantiate_reply("proxy0000")
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::UnknownReplyId { id: 42 });
+}
+
+#[test]
+fn test_create_proxy_with_salt() {
+    let mut deps = setup();
+    let msg = ExecuteMsg::CreateProxy {
+        pair_addr: PAIR.to_string(),
+        lp_token_addr: LP_TOKEN.to_string(),
+        reward_contract_addr: REWARD_CONTRACT.to_string(),
+        reward_token_addr: REWARD_TOKEN.to_string(),
+        label: None,
+        salt: Some(Binary::from(b"proxy".as_slice())),
+    };
+
+    #[cfg(not(feature = "cosmwasm_1_2"))]
+    {
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Instantiate2Unsupported {});
+    }
+
+    #[cfg(feature = "cosmwasm_1_2")]
+    {
+        let invalid_salt = ExecuteMsg::CreateProxy {
+            pair_addr: PAIR.to_string(),
+            lp_token_addr: LP_TOKEN.to_string(),
+            reward_contract_addr: REWARD_CONTRACT.to_string(),
+            reward_token_addr: REWARD_TOKEN.to_string(),
+            label: None,
+            salt: Some(Binary::default()),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            invalid_salt,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidSalt {});
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        match &res.messages[0].msg {
+            cosmwasm_std::CosmosMsg::Wasm(WasmMsg::Instantiate2 { code_id, salt, .. }) => {
+                assert_eq!(*code_id, PROXY_CODE_ID);
+                assert_eq!(salt, &Binary::from(b"proxy".as_slice()));
+            }
+            msg => panic!("unexpected message {:?}", msg),
+        }
+        assert_eq!(res.messages[0].id, INSTANTIATE_PROXY_REPLY_ID);
+
+        // the proxy address is read from the reply of instantiate2 as well
+        reply(deps.as_mut(), mock_env(), instantiate_reply("proxy0000")).unwrap();
+    }
+}
+
+#[test]
+fn test_proxies_pagination() {
+    let mut deps = setup();
+    for i in 1..=3 {
+        let lp_token = format!("lptoken000{}", i);
+        let reward_contract = format!("reward000{}", i);
+        deps.querier.ledger_mut().set_staking_config(
+            &reward_contract,
+            StakingConfig {
+                token: REWARD_TOKEN.to_string(),
+                pair: PAIR.to_string(),
+                lp_token: lp_token.clone(),
+            },
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            create_proxy_msg(&lp_token, &reward_contract),
+        )
+        .unwrap();
+        reply(
+            deps.as_mut(),
+            mock_env(),
+            instantiate_reply(&format!("proxy000{}", i)),
+        )
+        .unwrap();
+    }
+
+    let proxies = |start_after: Option<&str>, limit: Option<u32>| -> Vec<String> {
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Proxies {
+                start_after: start_after.map(String::from),
+                limit,
+            },
+        )
+        .unwrap();
+        from_binary::<ProxiesResponse>(&res)
+            .unwrap()
+            .proxies
+            .into_iter()
+            .map(|info| info.proxy.to_string())
+            .collect()
+    };
+
+    assert_eq!(
+        proxies(None, None),
+        vec!["proxy0001", "proxy0002", "proxy0003"]
+    );
+    assert_eq!(proxies(None, Some(2)), vec!["proxy0001", "proxy0002"]);
+    assert_eq!(proxies(Some("lptoken0002"), None), vec!["proxy0003"]);
+}
+
+#[test]
+fn test_update_config() {
+    let mut deps = setup();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        proxy_code_id: Some(8),
+        proxy_admin: Some("admin0001".to_string()),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.proxy_code_id, 8);
+    assert_eq!(config.proxy_admin, Some(Addr::unchecked("admin0001")));
+}
@@ -0,0 +1,167 @@
+use ap_valkyrie::factory::{ExecuteMsg, InstantiateMsg, ProxiesResponse, ProxyInfo, QueryMsg};
+use ap_valkyrie::lp_staking::QueryMsg as StakingQueryMsg;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult,
+};
+use cw_multi_test::{App, ContractWrapper, Executor};
+
+const OWNER: &str = "owner";
+const GENERATOR: &str = "generator";
+const PAIR: &str = "pair";
+const LP_TOKEN: &str = "lptoken";
+const REWARD_TOKEN: &str = "rewardtoken";
+
+/// Stand-in for the Valkyrie LP staking contract answering the config query only
+mod mock_staking {
+    use super::*;
+    use cw_storage_plus::Item;
+
+    #[cw_serde]
+    pub struct InstantiateMsg {
+        pub token: String,
+        pub pair: String,
+        pub lp_token: String,
+    }
+
+    const CONFIG: Item<InstantiateMsg> = Item::new("config");
+
+    pub fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        CONFIG.save(deps.storage, &msg)?;
+        Ok(Response::new())
+    }
+
+    pub fn execute(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        Err(StdError::generic_err("not supported"))
+    }
+
+    pub fn query(deps: Deps, _env: Env, msg: StakingQueryMsg) -> StdResult<Binary> {
+        match msg {
+            StakingQueryMsg::Config {} => to_binary(&CONFIG.load(deps.storage)?),
+            StakingQueryMsg::StakerInfo { .. } => Err(StdError::generic_err("not supported")),
+        }
+    }
+}
+
+fn store_staking_code(app: &mut App) -> u64 {
+    let contract = ContractWrapper::new(
+        mock_staking::execute,
+        mock_staking::instantiate,
+        mock_staking::query,
+    );
+    app.store_code(Box::new(contract))
+}
+
+fn store_proxy_code(app: &mut App) -> u64 {
+    use generator_proxy_to_vkr::contract;
+    let contract = ContractWrapper::new(contract::execute, contract::instantiate, contract::query)
+        .with_reply(contract::reply);
+    app.store_code(Box::new(contract))
+}
+
+fn store_factory_code(app: &mut App) -> u64 {
+    use generator_proxy_factory::contract;
+    let contract = ContractWrapper::new(contract::execute, contract::instantiate, contract::query)
+        .with_reply(contract::reply);
+    app.store_code(Box::new(contract))
+}
+
+#[test]
+fn factory_instantiates_and_registers_proxies() {
+    let mut app = App::default();
+    let owner = Addr::unchecked(OWNER);
+
+    let staking_code_id = store_staking_code(&mut app);
+    let staking = app
+        .instantiate_contract(
+            staking_code_id,
+            owner.clone(),
+            &mock_staking::InstantiateMsg {
+                token: REWARD_TOKEN.to_string(),
+                pair: PAIR.to_string(),
+                lp_token: LP_TOKEN.to_string(),
+            },
+            &[],
+            "staking",
+            None,
+        )
+        .unwrap();
+
+    let proxy_code_id = store_proxy_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
+    let factory = app
+        .instantiate_contract(
+            factory_code_id,
+            owner.clone(),
+            &InstantiateMsg {
+                proxy_code_id,
+                generator_contract_addr: GENERATOR.to_string(),
+                proxy_admin: Some(OWNER.to_string()),
+                owner: None,
+            },
+            &[],
+            "factory",
+            None,
+        )
+        .unwrap();
+
+    let create_proxy_msg = ExecuteMsg::CreateProxy {
+        pair_addr: PAIR.to_string(),
+        lp_token_addr: LP_TOKEN.to_string(),
+        reward_contract_addr: staking.to_string(),
+        reward_token_addr: REWARD_TOKEN.to_string(),
+        label: Some("proxy".to_string()),
+        salt: None,
+    };
+    app.execute_contract(owner.clone(), factory.clone(), &create_proxy_msg, &[])
+        .unwrap();
+
+    let info: ProxyInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &factory,
+            &QueryMsg::ProxyFor {
+                lp_token: LP_TOKEN.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(info.lp_token, LP_TOKEN);
+
+    // the registered proxy is owned by the factory owner and migratable by the proxy admin
+    let config: ap_valkyrie::ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&info.proxy, &ap_valkyrie::QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.lp_token_addr, LP_TOKEN);
+    assert_eq!(config.generator_contract_addr, GENERATOR);
+    assert_eq!(config.owner, OWNER);
+    let contract_info = app.contract_data(&info.proxy).unwrap();
+    assert_eq!(contract_info.code_id, proxy_code_id as usize);
+    assert_eq!(contract_info.admin, Some(owner.clone()));
+
+    let proxies: ProxiesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &factory,
+            &QueryMsg::Proxies {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(proxies.proxies, vec![info]);
+
+    // a second proxy for the same LP token is rejected
+    app.execute_contract(owner, factory, &create_proxy_msg, &[])
+        .unwrap_err();
+}
@@ -1,6 +1,8 @@
-use ap_valkyrie::MigrateMsg;
-use astroport::generator_proxy::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use cosmwasm_schema::write_api;
+use std::fs;
+use std::path::Path;
+
+use ap_valkyrie::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, QueryMsgV2};
+use cosmwasm_schema::{write_api, QueryResponses};
 
 fn main() {
     write_api! {
@@ -9,4 +11,14 @@ fn main() {
         execute: ExecuteMsg,
         migrate: MigrateMsg,
     }
+
+    // `response_to_v2.json` lists every v2 response, export the response of each v2 query
+    let raw_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("schema")
+        .join("raw");
+    for (query, schema) in QueryMsgV2::response_schemas().unwrap() {
+        let path = raw_dir.join(format!("response_to_v2_{}.json", query));
+        fs::write(&path, serde_json::to_string_pretty(&schema).unwrap() + "\n").unwrap();
+        println!("Exported {}", path.display());
+    }
 }
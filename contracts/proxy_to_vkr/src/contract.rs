@@ -1,31 +1,66 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, Event,
+    MessageInfo, Order, Reply, Response, StdError, StdResult, SubMsg, SubMsgResult, Uint128,
+    WasmMsg,
 };
 use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
 
 use crate::error::ContractError;
-use crate::state::{Config, CONFIG};
-use ap_valkyrie::MigrateMsg;
-use astroport::generator_proxy::{
-    CallbackMsg, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
+use crate::state::{
+    Config, HarvestContext, RewardConversion, RewardSplit, RewardStream, Settings, SplitState,
+    StakingMigrationContext, WithdrawContext, BONDED, CONFIG, HARVEST_CONTEXT, HELD_LP,
+    LAST_HARVEST, OWNER, PENDING_BOND, PENDING_WITHDRAWALS, RESERVED_LP, REWARD_CONVERSION,
+    REWARD_SPLITS, REWARD_STREAM, SETTINGS, SPLIT_ACCRUED, SPLIT_STATE, STAKING_MIGRATION_CONTEXT,
+    STATUS, SURPLUS_BONDED, WITHDRAW_CONTEXT,
 };
-
-use cw2::set_contract_version;
-use valkyrie::lp_staking::execute_msgs::{
-    Cw20HookMsg as VkrCw20HookMsg, ExecuteMsg as VkrExecuteMsg,
+use ap_valkyrie::{
+    ConfigDivergence, ConfigResponse, Cw20HookMsg, DepositResponse, ExecuteMsg,
+    HarvestInfoResponse, HealthCheckResponse, HealthResponse, IdleBalanceResponse, InstantiateMsg,
+    MigrateMsg, PendingTokenResponse, PendingWithdrawal, PendingWithdrawalsResponse, ProxyStatus,
+    QueryMsg, QueryMsgV2, RewardConversionInfo, RewardConversionResponse, RewardInfoResponse,
+    RewardResponse, RewardSplitInfo, RewardSplitResponse, SettingsResponse, StreamStateResponse,
+    INVARIANT_BOND_AMOUNT, INVARIANT_NO_IDLE_LP, INVARIANT_UPSTREAM_CONFIG,
 };
-use valkyrie::lp_staking::query_msgs::{QueryMsg as VkrQueryMsg, StakerInfoResponse};
+
+use ap_valkyrie::lp_staking::{self, ConfigResponse as VkrConfigResponse, StakerInfoResponse};
+use ap_valkyrie::router::{self, AssetInfo};
+use cw2::{get_contract_version, set_contract_version};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "astroport-generator-proxy-to-vkr";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Reply of the unbond submessage sent on withdraw
+pub(crate) const WITHDRAW_REPLY_ID: u64 = 1;
+/// Reply of the unbond submessage sent to the old staking contract on staking migration
+pub(crate) const MIGRATION_UNBOND_REPLY_ID: u64 = 2;
+/// Reply of the bond submessage sent to the new staking contract on staking migration
+pub(crate) const MIGRATION_BOND_REPLY_ID: u64 = 3;
+/// Reply of the bond submessage sent on deposit when LP tokens are held on bond failure
+pub(crate) const BOND_REPLY_ID: u64 = 4;
+/// Reply of the reward claim submessage sent on harvest
+pub(crate) const HARVEST_REPLY_ID: u64 = 5;
+/// Reply of the reward claim submessage sent on reward update when rewards are streamed
+pub(crate) const STREAM_REPLY_ID: u64 = 6;
+/// Reply of the reward claim submessage sent on reward update when rewards are converted
+pub(crate) const CONVERT_REPLY_ID: u64 = 7;
+/// Reply of the swap submessage converting claimed rewards
+pub(crate) const SWAP_REPLY_ID: u64 = 8;
+
+/// The harvest bounty can not exceed this share of the harvested rewards, 10%
+const MAX_HARVEST_BOUNTY: Decimal = Decimal::raw(100_000_000_000_000_000);
+
+/// The max spread of every swap of the reward conversion can not exceed this value, 5%. Without a
+/// belief price the spread is measured against the current pool prices only
+const MAX_CONVERSION_SPREAD: Decimal = Decimal::raw(50_000_000_000_000_000);
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
@@ -37,11 +72,48 @@ pub fn instantiate(
         reward_contract_addr: deps.api.addr_validate(&msg.reward_contract_addr)?,
         reward_token_addr: deps.api.addr_validate(&msg.reward_token_addr)?,
     };
+
+    validate_staking_contract(deps.as_ref(), &config)?;
+
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+
     CONFIG.save(deps.storage, &config)?;
+    OWNER.save(deps.storage, &owner)?;
+    BONDED.save(deps.storage, &Uint128::zero())?;
+    RESERVED_LP.save(deps.storage, &Uint128::zero())?;
+    HELD_LP.save(deps.storage, &Uint128::zero())?;
+    SURPLUS_BONDED.save(deps.storage, &Uint128::zero())?;
+    LAST_HARVEST.save(deps.storage, &0)?;
+    REWARD_STREAM.save(deps.storage, &RewardStream::default())?;
+    REWARD_SPLITS.save(deps.storage, &vec![])?;
+    SPLIT_STATE.save(deps.storage, &SplitState::default())?;
+    SETTINGS.save(deps.storage, &Settings::default())?;
+    STATUS.save(deps.storage, &ProxyStatus::Active)?;
 
     Ok(Response::default())
 }
 
+/// @dev The reward token and LP token must match the ones used by the VKR LP staking contract
+fn validate_staking_contract(deps: Deps, cfg: &Config) -> Result<(), ContractError> {
+    let upstream = query_upstream_config(deps, cfg)?;
+    if upstream.token != cfg.reward_token_addr.as_str() {
+        return Err(ContractError::RewardTokenMismatch {
+            expected: cfg.reward_token_addr.to_string(),
+            actual: upstream.token,
+        });
+    }
+    if upstream.lp_token != cfg.lp_token_addr.as_str() {
+        return Err(ContractError::LpTokenMismatch {
+            expected: cfg.lp_token_addr.to_string(),
+            actual: upstream.lp_token,
+        });
+    }
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -51,36 +123,236 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
-        ExecuteMsg::UpdateRewards {} => update_rewards(deps, info),
-        ExecuteMsg::SendRewards { account, amount } => send_rewards(deps, info, account, amount),
-        ExecuteMsg::Withdraw { account, amount } => withdraw(deps, env, info, account, amount),
+        ExecuteMsg::UpdateRewards {} => update_rewards(deps, env, info),
+        ExecuteMsg::SendRewards { account, amount } => {
+            send_rewards(deps, env, info, account, amount)
+        }
+        ExecuteMsg::Withdraw { account, amount } => {
+            let claim_rewards = SETTINGS.load(deps.storage)?.claim_rewards_on_withdraw;
+            withdraw(deps, env, info, account, amount, claim_rewards)
+        }
         ExecuteMsg::EmergencyWithdraw { account, amount } => {
-            withdraw(deps, env, info, account, amount)
+            withdraw(deps, env, info, account, amount, false)
+        }
+        ExecuteMsg::RestakeIdle {} => restake_idle(deps, env, info),
+        ExecuteMsg::UpdateSettings {
+            claim_rewards_on_withdraw,
+            unbonding_period,
+            hold_lp_on_bond_failure,
+            harvest_interval,
+            harvest_bounty,
+            min_claim_amount,
+            reward_stream_period,
+        } => update_settings(
+            deps,
+            env,
+            info,
+            claim_rewards_on_withdraw,
+            unbonding_period,
+            hold_lp_on_bond_failure,
+            harvest_interval,
+            harvest_bounty,
+            min_claim_amount,
+            reward_stream_period,
+        ),
+        ExecuteMsg::ClaimUnbonded { account } => claim_unbonded(deps, env, account),
+        ExecuteMsg::MigrateStakingContract {
+            new_reward_contract,
+        } => migrate_staking_contract(deps, env, info, new_reward_contract),
+        ExecuteMsg::Deprecate {} => deprecate(deps, env, info),
+        ExecuteMsg::Harvest {} => harvest(deps, env, info),
+        ExecuteMsg::UpdateRewardConversion { conversion } => {
+            update_reward_conversion(deps, env, info, conversion)
         }
-        ExecuteMsg::Callback(msg) => handle_callback(deps, env, info, msg),
+        ExecuteMsg::UpdateRewardSplits { splits } => update_reward_splits(deps, env, info, splits),
+        ExecuteMsg::ClaimSplit {} => claim_split(deps, env, info),
     }
 }
 
-pub fn handle_callback(
-    deps: DepsMut,
+/// @dev Deposits, restaking and staking migrations are only available while the proxy is active
+fn assert_active(deps: Deps) -> Result<(), ContractError> {
+    if STATUS.load(deps.storage)? == ProxyStatus::Deprecated {
+        return Err(ContractError::ProxyDeprecated {});
+    }
+    Ok(())
+}
+
+/// @dev Updates the optional proxy behaviour. Can only be called by the owner
+/// @param claim_rewards_on_withdraw : Whether rewards are claimed before LP tokens are unbonded
+/// @param unbonding_period : Seconds withdrawn LP tokens are queued for, zero pays them out instantly
+/// @param hold_lp_on_bond_failure : Whether deposited LP tokens are held when bonding fails
+/// @param harvest_interval : Minimum number of seconds between two harvests
+/// @param harvest_bounty : Share of the harvested rewards paid to the caller of Harvest
+/// @param min_claim_amount : Pending rewards below this amount are not claimed by UpdateRewards
+/// @param reward_stream_period : Seconds claimed rewards are released over, zero releases them at once
+#[allow(clippy::too_many_arguments)]
+fn update_settings(
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    msg: CallbackMsg,
+    claim_rewards_on_withdraw: Option<bool>,
+    unbonding_period: Option<u64>,
+    hold_lp_on_bond_failure: Option<bool>,
+    harvest_interval: Option<u64>,
+    harvest_bounty: Option<Decimal>,
+    min_claim_amount: Option<Uint128>,
+    reward_stream_period: Option<u64>,
 ) -> Result<Response, ContractError> {
-    // Callback functions can only be called this contract itself
-    if info.sender != env.contract.address {
+    if info.sender != OWNER.load(deps.storage)? {
         return Err(ContractError::Unauthorized {});
     }
-    match msg {
-        CallbackMsg::TransferLpTokensAfterWithdraw {
-            account,
-            prev_lp_balance,
-        } => transfer_lp_tokens_after_withdraw(deps, env, account, prev_lp_balance),
+
+    let mut settings = SETTINGS.load(deps.storage)?;
+    if let Some(claim_rewards_on_withdraw) = claim_rewards_on_withdraw {
+        settings.claim_rewards_on_withdraw = claim_rewards_on_withdraw;
+    }
+    if let Some(unbonding_period) = unbonding_period {
+        settings.unbonding_period = unbonding_period;
+    }
+    if let Some(hold_lp_on_bond_failure) = hold_lp_on_bond_failure {
+        settings.hold_lp_on_bond_failure = hold_lp_on_bond_failure;
+    }
+    if let Some(harvest_interval) = harvest_interval {
+        settings.harvest_interval = harvest_interval;
+    }
+    if let Some(harvest_bounty) = harvest_bounty {
+        if harvest_bounty > MAX_HARVEST_BOUNTY {
+            return Err(ContractError::HarvestBountyTooHigh {
+                max: MAX_HARVEST_BOUNTY,
+            });
+        }
+        settings.harvest_bounty = harvest_bounty;
+    }
+    // without an interval the bounty could be collected on every block
+    if !settings.harvest_bounty.is_zero() && settings.harvest_interval == 0 {
+        return Err(ContractError::HarvestIntervalRequired {});
+    }
+    if let Some(min_claim_amount) = min_claim_amount {
+        settings.min_claim_amount = min_claim_amount;
+    }
+    if let Some(reward_stream_period) = reward_stream_period {
+        // rewards claimed so far are streamed with the previous period
+        let cfg = CONFIG.load(deps.storage)?;
+        let reward_balance = split_reward_balance(deps.branch(), &env, &cfg)?;
+        stream_claimed_rewards(deps.branch(), &env, reward_balance)?;
+        settings.reward_stream_period = reward_stream_period;
+    }
+    SETTINGS.save(deps.storage, &settings)?;
+
+    Ok(Response::new().add_attribute("action", "update_settings"))
+}
+
+/// @dev Sets or disables the conversion of claimed rewards through the Astroport router. Can only
+/// be called by the owner. The generator accounts rewards in the distributed token, so it can only
+/// change while the proxy holds no deposits
+/// @param conversion : The router, the route, the max spread and the belief price, None disables
+/// the conversion
+fn update_reward_conversion(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    conversion: Option<RewardConversionInfo>,
+) -> Result<Response, ContractError> {
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let conversion = conversion
+        .map(|conversion| -> Result<_, ContractError> {
+            let target_token = match conversion.route.last() {
+                Some(AssetInfo::Token { contract_addr }) => {
+                    deps.api.addr_validate(contract_addr.as_str())?
+                }
+                Some(AssetInfo::NativeToken { .. }) => {
+                    return Err(ContractError::NativeConversionTarget {})
+                }
+                None => return Err(ContractError::EmptyConversionRoute {}),
+            };
+            if conversion.max_spread > MAX_CONVERSION_SPREAD {
+                return Err(ContractError::ConversionSpreadTooHigh {
+                    max: MAX_CONVERSION_SPREAD,
+                });
+            }
+            if conversion.belief_price == Some(Decimal::zero()) {
+                return Err(ContractError::ZeroBeliefPrice {});
+            }
+
+            Ok(RewardConversion {
+                router: deps.api.addr_validate(&conversion.router)?,
+                operations: router::route_operations(&cfg.reward_token_addr, &conversion.route),
+                target_token,
+                max_spread: conversion.max_spread,
+                belief_price: conversion.belief_price,
+            })
+        })
+        .transpose()?;
+
+    let prev_token = query_distributed_token(deps.as_ref(), &cfg)?;
+    let distributed_token = match &conversion {
+        Some(conversion) => conversion.target_token.clone(),
+        None => cfg.reward_token_addr.clone(),
+    };
+    if distributed_token != prev_token {
+        // rewards accrued to the beneficiaries are held in the previous token
+        split_reward_balance(deps.branch(), &env, &cfg)?;
+        if !query_deposit(deps.as_ref(), &env, &cfg)?.is_zero()
+            || !RESERVED_LP.load(deps.storage)?.is_zero()
+            || !SPLIT_STATE.load(deps.storage)?.accrued_amount.is_zero()
+        {
+            return Err(ContractError::DistributedTokenLocked {});
+        }
+
+        // the stream and the split restart from the balance of the new token
+        let now = env.block.time.seconds();
+        let reward_balance = query_token_balance(deps.as_ref(), &env, &distributed_token)?;
+        REWARD_STREAM.save(
+            deps.storage,
+            &RewardStream {
+                start_time: now,
+                end_time: now,
+                locked_amount: Uint128::zero(),
+                accounted_balance: reward_balance,
+            },
+        )?;
+        SPLIT_STATE.save(
+            deps.storage,
+            &SplitState {
+                accounted_balance: reward_balance,
+                accrued_amount: Uint128::zero(),
+            },
+        )?;
+    }
+
+    match conversion {
+        Some(conversion) => REWARD_CONVERSION.save(deps.storage, &conversion)?,
+        None => REWARD_CONVERSION.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_reward_conversion"),
+        attr("distributed_token", distributed_token),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        WITHDRAW_REPLY_ID => transfer_lp_tokens_after_withdraw(deps, env),
+        MIGRATION_UNBOND_REPLY_ID => bond_into_new_staking_contract(deps, env),
+        MIGRATION_BOND_REPLY_ID => finish_staking_migration(deps, env),
+        BOND_REPLY_ID => settle_pending_bond(deps, msg.result),
+        HARVEST_REPLY_ID => pay_harvest_bounty(deps, env),
+        STREAM_REPLY_ID => stream_rewards_after_claim(deps, env),
+        CONVERT_REPLY_ID => convert_rewards_after_claim(deps, env),
+        SWAP_REPLY_ID => finish_reward_conversion(deps, env, msg.result),
+        id => Err(ContractError::UnknownReplyId { id }),
     }
 }
 
 /// @dev Receives LP tokens sent by Generator contract.
-/// Stakes them with the VKR LP Staking contract
+/// Stakes them with the VKR LP Staking contract. If enabled in the settings, a failed bond keeps
+/// the LP tokens in the proxy instead of reverting the deposit
 fn receive_cw20(
     deps: DepsMut,
     _env: Env,
@@ -94,47 +366,475 @@ fn receive_cw20(
         if cw20_msg.sender != cfg.generator_contract_addr || info.sender != cfg.lp_token_addr {
             return Err(ContractError::Unauthorized {});
         }
-        response
-            .messages
-            .push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: cfg.lp_token_addr.to_string(),
-                funds: vec![],
-                msg: to_binary(&Cw20ExecuteMsg::Send {
-                    contract: cfg.reward_contract_addr.to_string(),
-                    amount: cw20_msg.amount,
-                    msg: to_binary(&VkrCw20HookMsg::Bond {})?,
-                })?,
-            })));
+        assert_active(deps.as_ref())?;
+        BONDED.update(deps.storage, |bonded| -> StdResult<_> {
+            Ok(bonded.checked_add(cw20_msg.amount)?)
+        })?;
+        let bond_msg = bond_msg(&cfg, cw20_msg.amount)?;
+        if SETTINGS.load(deps.storage)?.hold_lp_on_bond_failure {
+            PENDING_BOND.save(deps.storage, &cw20_msg.amount)?;
+            response
+                .messages
+                .push(SubMsg::reply_always(bond_msg, BOND_REPLY_ID));
+        } else {
+            response.messages.push(SubMsg::new(bond_msg));
+        }
     } else {
         return Err(ContractError::IncorrectCw20HookMessageVariant {});
     }
     Ok(response)
 }
 
-/// @dev Claims pending rewards from the VKR LP staking contract
-fn update_rewards(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+/// @dev Clears the pending bond once the VKR LP staking contract replies. When the bond is
+/// rejected, the deposited LP tokens are kept in the proxy and tracked as held until restaked with
+/// RestakeIdle
+/// @param result : The result of the bond submessage
+fn settle_pending_bond(deps: DepsMut, result: SubMsgResult) -> Result<Response, ContractError> {
+    let amount = PENDING_BOND.load(deps.storage)?;
+    PENDING_BOND.remove(deps.storage);
+    let error = match result {
+        SubMsgResult::Err(error) => error,
+        SubMsgResult::Ok(_) => return Ok(Response::new()),
+    };
+
+    BONDED.update(deps.storage, |bonded| -> StdResult<_> {
+        Ok(bonded.checked_sub(amount)?)
+    })?;
+    HELD_LP.update(deps.storage, |held| -> StdResult<_> {
+        Ok(held.checked_add(amount)?)
+    })?;
+
+    Ok(Response::new().add_event(
+        Event::new("bond_failed")
+            .add_attribute("amount", amount)
+            .add_attribute("error", error),
+    ))
+}
+
+/// @dev Builds the message that stakes LP tokens held by the proxy with the VKR LP Staking contract
+fn bond_msg(cfg: &Config, amount: Uint128) -> StdResult<CosmosMsg> {
+    lp_staking::bond_msg(&cfg.lp_token_addr, &cfg.reward_contract_addr, amount)
+}
+
+/// @dev Stakes the LP tokens held by the proxy and not reserved for withdrawals with the VKR LP
+/// Staking contract. The deposited LP tokens held after a failed bond are bonded back into the
+/// deposit, the other LP tokens, e.g. donations, are bonded as surplus outside of the deposit
+fn restake_idle(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if info.sender != cfg.generator_contract_addr && info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    assert_active(deps.as_ref())?;
+
+    let IdleBalanceResponse {
+        idle_amount,
+        held_amount,
+        surplus_amount,
+        ..
+    } = query_idle_balance(deps.as_ref(), &env, &cfg)?;
+    if idle_amount.is_zero() {
+        return Err(ContractError::NoIdleLpTokens {});
+    }
+    let held_amount = held_amount.min(idle_amount);
+
+    BONDED.update(deps.storage, |bonded| -> StdResult<_> {
+        Ok(bonded.checked_add(held_amount)?)
+    })?;
+    HELD_LP.update(deps.storage, |held| -> StdResult<_> {
+        Ok(held.checked_sub(held_amount)?)
+    })?;
+    SURPLUS_BONDED.update(deps.storage, |surplus| -> StdResult<_> {
+        Ok(surplus.checked_add(surplus_amount)?)
+    })?;
+
+    Ok(Response::new()
+        .add_message(bond_msg(&cfg, idle_amount)?)
+        .add_attributes(vec![
+            attr("action", "restake_idle"),
+            attr("amount", idle_amount),
+            attr("held_amount", held_amount),
+            attr("surplus_amount", surplus_amount),
+        ]))
+}
+
+/// @dev Claims pending rewards from the VKR LP staking contract. The claim is skipped when no
+/// rewards are pending or they are below the minimum claim amount. When rewards are streamed, the
+/// claimed rewards are added to the stream once the claim replies. When rewards are converted, the
+/// reward token balance is swapped once the claim replies, or right away if nothing is claimed
+fn update_rewards(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
     let mut response = Response::new();
     let cfg = CONFIG.load(deps.storage)?;
     if info.sender != cfg.generator_contract_addr {
         return Err(ContractError::Unauthorized {});
     };
 
-    response
-        .messages
-        .push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: cfg.reward_contract_addr.to_string(),
+    let settings = SETTINGS.load(deps.storage)?;
+    let streaming = settings.reward_stream_period > 0;
+    let converting = REWARD_CONVERSION.may_load(deps.storage)?.is_some();
+    if streaming {
+        // rewards claimed outside of reward updates, e.g. on withdraw or deprecation
+        let reward_balance = split_reward_balance(deps.branch(), &env, &cfg)?;
+        stream_claimed_rewards(deps.branch(), &env, reward_balance)?;
+    }
+
+    // the final rewards were claimed on deprecation
+    if STATUS.load(deps.storage)? == ProxyStatus::Deprecated {
+        if converting {
+            response
+                .messages
+                .extend(convert_rewards_msg(deps.as_ref(), &env, &cfg)?);
+        }
+        return Ok(response);
+    }
+
+    let pending_reward = query_staker_info(deps.as_ref(), &env, &cfg)?.pending_reward;
+    response = response.add_attributes(vec![
+        attr("action", "update_rewards"),
+        attr("pending_reward", pending_reward),
+    ]);
+    if pending_reward.is_zero() || pending_reward < settings.min_claim_amount {
+        if converting {
+            response
+                .messages
+                .extend(convert_rewards_msg(deps.as_ref(), &env, &cfg)?);
+        }
+        return Ok(response.add_attribute("claim_skipped", "true"));
+    }
+
+    let withdraw_msg = lp_staking::withdraw_msg(&cfg.reward_contract_addr)?;
+    response.messages.push(if converting {
+        SubMsg::reply_on_success(withdraw_msg, CONVERT_REPLY_ID)
+    } else if streaming {
+        SubMsg::reply_on_success(withdraw_msg, STREAM_REPLY_ID)
+    } else {
+        SubMsg::new(withdraw_msg)
+    });
+
+    Ok(response)
+}
+
+/// @dev Adds the rewards claimed by the reward update waiting for the reply to the stream
+fn stream_rewards_after_claim(mut deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let reward_balance = split_reward_balance(deps.branch(), &env, &cfg)?;
+    let stream = stream_claimed_rewards(deps, &env, reward_balance)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "stream_rewards"),
+        attr("locked_amount", stream.locked_amount),
+        attr("end_time", stream.end_time.to_string()),
+    ]))
+}
+
+/// @dev Converts the rewards claimed by the reward update waiting for the reply
+fn convert_rewards_after_claim(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(Response::new().add_submessages(convert_rewards_msg(deps.as_ref(), &env, &cfg)?))
+}
+
+/// @dev Returns the submessage swapping the whole reward token balance of the proxy to the
+/// conversion target, if any. With a belief price, the swap must return at least the amount at this
+/// price minus the max spread. The swap replies whether it succeeds or not
+fn convert_rewards_msg(deps: Deps, env: &Env, cfg: &Config) -> StdResult<Option<SubMsg>> {
+    let conversion = REWARD_CONVERSION.load(deps.storage)?;
+    let amount = query_token_balance(deps, env, &cfg.reward_token_addr)?;
+    if amount.is_zero() {
+        return Ok(None);
+    }
+
+    // the current pool prices can be moved within the same transaction, the belief price can't
+    let minimum_receive = conversion
+        .belief_price
+        .map(|price| amount * price * (Decimal::one() - conversion.max_spread));
+
+    Ok(Some(SubMsg::reply_always(
+        router::swap_msg(
+            &cfg.reward_token_addr,
+            &conversion.router,
+            amount,
+            conversion.operations,
+            minimum_receive,
+            conversion.max_spread,
+        )?,
+        SWAP_REPLY_ID,
+    )))
+}
+
+/// @dev Reports the result of the reward conversion waiting for the reply. A failed swap, e.g. over
+/// the max spread, keeps the reward tokens in the proxy until the next reward update. The
+/// converted rewards are added to the stream when rewards are streamed
+/// @param result : The result of the swap submessage
+fn finish_reward_conversion(
+    mut deps: DepsMut,
+    env: Env,
+    result: SubMsgResult,
+) -> Result<Response, ContractError> {
+    let mut response = Response::new().add_attribute("action", "convert_rewards");
+    if let SubMsgResult::Err(error) = result {
+        response = response.add_attribute("conversion_failed", error);
+    }
+
+    if SETTINGS.load(deps.storage)?.reward_stream_period > 0 {
+        let cfg = CONFIG.load(deps.storage)?;
+        let reward_balance = split_reward_balance(deps.branch(), &env, &cfg)?;
+        let stream = stream_claimed_rewards(deps, &env, reward_balance)?;
+        response = response.add_attributes(vec![
+            attr("locked_amount", stream.locked_amount),
+            attr("end_time", stream.end_time.to_string()),
+        ]);
+    }
+    Ok(response)
+}
+
+/// @dev Adds the rewards the proxy received since the last update of the stream to it. Rewards
+/// still locked are released together with the new ones over a whole stream period, without new
+/// rewards or with streaming disabled they keep being released at the same rate
+/// @param reward_balance : The reward token balance of the proxy
+fn stream_claimed_rewards(
+    deps: DepsMut,
+    env: &Env,
+    reward_balance: Uint128,
+) -> StdResult<RewardStream> {
+    let stream_period = SETTINGS.load(deps.storage)?.reward_stream_period;
+    let stream = REWARD_STREAM.load(deps.storage)?;
+    let now = env.block.time.seconds();
+
+    let claimed = reward_balance.saturating_sub(stream.accounted_balance);
+    let locked_amount = stream.locked_at(now);
+    let stream = if stream_period == 0 || claimed.is_zero() {
+        RewardStream {
+            start_time: now,
+            end_time: stream.end_time.max(now),
+            locked_amount,
+            accounted_balance: reward_balance,
+        }
+    } else {
+        RewardStream {
+            start_time: now,
+            end_time: now + stream_period,
+            locked_amount: locked_amount + claimed,
+            accounted_balance: reward_balance,
+        }
+    };
+    REWARD_STREAM.save(deps.storage, &stream)?;
+
+    Ok(stream)
+}
+
+/// @dev Sets the beneficiaries claimed rewards are split among. Can only be called by the owner.
+/// The rewards received so far are split among the previous beneficiaries
+/// @param splits : The beneficiaries and their weights, an empty list disables the split
+fn update_reward_splits(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    splits: Vec<RewardSplitInfo>,
+) -> Result<Response, ContractError> {
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut validated: Vec<RewardSplit> = Vec::with_capacity(splits.len());
+    for split in splits {
+        let recipient = deps.api.addr_validate(&split.recipient)?;
+        if validated.iter().any(|split| split.recipient == recipient) {
+            return Err(ContractError::DuplicateSplitRecipient {
+                recipient: recipient.to_string(),
+            });
+        }
+        if split.weight.is_zero() {
+            return Err(ContractError::InvalidSplitWeights {});
+        }
+        validated.push(RewardSplit {
+            recipient,
+            weight: split.weight,
+        });
+    }
+    let total_weight = validated
+        .iter()
+        .try_fold(Decimal::zero(), |total, split| {
+            total.checked_add(split.weight)
+        })
+        .map_err(|_| ContractError::InvalidSplitWeights {})?;
+    if !validated.is_empty() && total_weight != Decimal::one() {
+        return Err(ContractError::InvalidSplitWeights {});
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
+    split_reward_balance(deps.branch(), &env, &cfg)?;
+    REWARD_SPLITS.save(deps.storage, &validated)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_reward_splits"),
+        attr("beneficiaries", validated.len().to_string()),
+    ]))
+}
+
+/// @dev Transfers the rewards accrued to the calling beneficiary
+fn claim_split(mut deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    split_reward_balance(deps.branch(), &env, &cfg)?;
+
+    let amount = SPLIT_ACCRUED
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if amount.is_zero() {
+        return Err(ContractError::NoAccruedRewards {
+            recipient: info.sender.to_string(),
+        });
+    }
+    SPLIT_ACCRUED.remove(deps.storage, &info.sender);
+
+    // the claimed rewards leave both the split balance and the accrued rewards, the balance left
+    // to the generator is unchanged
+    let mut split_state = SPLIT_STATE.load(deps.storage)?;
+    split_state.accounted_balance = split_state.accounted_balance.saturating_sub(amount);
+    split_state.accrued_amount = split_state.accrued_amount.saturating_sub(amount);
+    SPLIT_STATE.save(deps.storage, &split_state)?;
+
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: query_distributed_token(deps.as_ref(), &cfg)?.to_string(),
             funds: vec![],
-            msg: to_binary(&VkrExecuteMsg::Withdraw {})?,
-        })));
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+            })?,
+        })
+        .add_attributes(vec![
+            attr("action", "claim_split"),
+            attr("recipient", &info.sender),
+            attr("amount", amount),
+        ]))
+}
+
+/// @dev Splits the distributed token the proxy received since the last split among the
+/// beneficiaries and returns the balance left to the generator
+fn split_reward_balance(deps: DepsMut, env: &Env, cfg: &Config) -> StdResult<Uint128> {
+    let distributed_token = query_distributed_token(deps.as_ref(), cfg)?;
+    let reward_balance = query_token_balance(deps.as_ref(), env, &distributed_token)?;
+    split_claimed_rewards(deps, cfg, reward_balance)
+}
+
+/// @dev Accrues the share of every beneficiary in the rewards the proxy received since the last
+/// split. The share of the generator stays in the balance it distributes
+/// @param reward_balance : The distributed token balance of the proxy
+fn split_claimed_rewards(
+    deps: DepsMut,
+    cfg: &Config,
+    reward_balance: Uint128,
+) -> StdResult<Uint128> {
+    let mut split_state = SPLIT_STATE.load(deps.storage)?;
+    for (recipient, share) in split_shares(deps.as_ref(), cfg, reward_balance)? {
+        if share.is_zero() {
+            continue;
+        }
+        SPLIT_ACCRUED.update(deps.storage, &recipient, |accrued| -> StdResult<_> {
+            Ok(accrued.unwrap_or_default() + share)
+        })?;
+        split_state.accrued_amount += share;
+    }
+    split_state.accounted_balance = reward_balance;
+    SPLIT_STATE.save(deps.storage, &split_state)?;
+
+    Ok(reward_balance.saturating_sub(split_state.accrued_amount))
+}
 
+/// @dev Returns the share of every beneficiary but the generator in the rewards the proxy received
+/// since the last split
+/// @param reward_balance : The distributed token balance of the proxy
+fn split_shares(
+    deps: Deps,
+    cfg: &Config,
+    reward_balance: Uint128,
+) -> StdResult<Vec<(Addr, Uint128)>> {
+    let received = reward_balance.saturating_sub(SPLIT_STATE.load(deps.storage)?.accounted_balance);
+    Ok(REWARD_SPLITS
+        .load(deps.storage)?
+        .into_iter()
+        .filter(|split| split.recipient != cfg.generator_contract_addr)
+        .map(|split| (split.recipient, received * split.weight))
+        .collect())
+}
+
+/// @dev Claims pending rewards from the VKR LP staking contract on behalf of anyone, at most once
+/// per harvest interval. The caller is paid the harvest bounty once the claim replies
+fn harvest(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    assert_active(deps.as_ref())?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let next_harvest = query_harvest_info(deps.as_ref())?.next_harvest;
+    if env.block.time.seconds() < next_harvest {
+        return Err(ContractError::HarvestTooSoon { next_harvest });
+    }
+    LAST_HARVEST.save(deps.storage, &env.block.time.seconds())?;
+
+    let prev_reward_balance = query_token_balance(deps.as_ref(), &env, &cfg.reward_token_addr)?;
+    HARVEST_CONTEXT.save(
+        deps.storage,
+        &HarvestContext {
+            keeper: info.sender,
+            prev_reward_balance,
+        },
+    )?;
+
+    Ok(Response::new().add_submessage(SubMsg::reply_on_success(
+        lp_staking::withdraw_msg(&cfg.reward_contract_addr)?,
+        HARVEST_REPLY_ID,
+    )))
+}
+
+/// @dev Pays the harvest bounty out of the rewards claimed by the harvest waiting for the reply.
+/// When rewards are streamed, the rest of the claimed rewards is added to the stream
+fn pay_harvest_bounty(mut deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let HarvestContext {
+        keeper,
+        prev_reward_balance,
+    } = HARVEST_CONTEXT.load(deps.storage)?;
+    HARVEST_CONTEXT.remove(deps.storage);
+
+    let reward_balance = query_token_balance(deps.as_ref(), &env, &cfg.reward_token_addr)?;
+    let amount = reward_balance
+        .checked_sub(prev_reward_balance)
+        .map_err(StdError::from)?;
+    let settings = SETTINGS.load(deps.storage)?;
+    let bounty = amount * settings.harvest_bounty;
+    // converted rewards are streamed by the reward update converting them
+    if settings.reward_stream_period > 0 && REWARD_CONVERSION.may_load(deps.storage)?.is_none() {
+        let reward_balance = split_claimed_rewards(deps.branch(), &cfg, reward_balance - bounty)?;
+        stream_claimed_rewards(deps, &env, reward_balance)?;
+    }
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("action", "harvest"),
+        attr("keeper", &keeper),
+        attr("amount", amount),
+        attr("bounty", bounty),
+    ]);
+    if !bounty.is_zero() {
+        response = response.add_message(WasmMsg::Execute {
+            contract_addr: cfg.reward_token_addr.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: keeper.to_string(),
+                amount: bounty,
+            })?,
+        });
+    }
     Ok(response)
 }
 
-/// @dev Transfers VKR rewards
-/// @param account : User to which VKR tokens are to be transferred
-/// @param amount : Number of VKR to be transferred
+/// @dev Transfers VKR rewards, or the tokens they are converted to
+/// @param account : User to which the tokens are to be transferred
+/// @param amount : Number of tokens to be transferred
 fn send_rewards(
-    deps: DepsMut,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     account: String,
     amount: Uint128,
@@ -145,10 +845,22 @@ fn send_rewards(
         return Err(ContractError::Unauthorized {});
     };
 
+    // the sent rewards leave the balance accounted for by the split and the stream
+    let distributed_token = query_distributed_token(deps.as_ref(), &cfg)?;
+    let reward_balance = split_reward_balance(deps.branch(), &env, &cfg)?;
+    let mut split_state = SPLIT_STATE.load(deps.storage)?;
+    split_state.accounted_balance = split_state.accounted_balance.saturating_sub(amount);
+    SPLIT_STATE.save(deps.storage, &split_state)?;
+    if SETTINGS.load(deps.storage)?.reward_stream_period > 0 {
+        let mut stream = stream_claimed_rewards(deps.branch(), &env, reward_balance)?;
+        stream.accounted_balance = stream.accounted_balance.saturating_sub(amount);
+        REWARD_STREAM.save(deps.storage, &stream)?;
+    }
+
     response
         .messages
         .push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: cfg.reward_token_addr.to_string(),
+            contract_addr: distributed_token.to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Transfer {
                 recipient: account,
                 amount,
@@ -158,21 +870,47 @@ fn send_rewards(
     Ok(response)
 }
 
-/// @dev Withdraws LP Tokens from the staking contract. Rewards are NOT claimed when withdrawing LP tokens
+/// @dev Withdraws LP Tokens. LP tokens held by the proxy after a failed bond are paid out first,
+/// only the remainder is unbonded from the staking contract. Rewards are claimed beforehand only if
+/// requested
 /// @param account : User to which LP tokens are to be transferred
-/// @param amount : Number of LP to be unstaked and transferred
+/// @param amount : Number of LP to be transferred
+/// @param claim_rewards : Whether pending rewards are claimed before unbonding
 fn withdraw(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     account: String,
     amount: Uint128,
+    claim_rewards: bool,
 ) -> Result<Response, ContractError> {
     let mut response = Response::new();
     let cfg = CONFIG.load(deps.storage)?;
     if info.sender != cfg.generator_contract_addr {
         return Err(ContractError::Unauthorized {});
     };
+
+    // LP tokens of a deprecated proxy are already held by the proxy
+    if STATUS.load(deps.storage)? == ProxyStatus::Deprecated {
+        RESERVED_LP.update(deps.storage, |reserved| -> StdResult<_> {
+            Ok(reserved.checked_sub(amount)?)
+        })?;
+        return Ok(response
+            .add_message(WasmMsg::Execute {
+                contract_addr: cfg.lp_token_addr.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: account.clone(),
+                    amount,
+                })?,
+            })
+            .add_attributes(vec![
+                attr("action", "withdraw_deprecated"),
+                attr("account", account),
+                attr("amount", amount),
+            ]));
+    }
+
     // current LP Tokens balance
     let prev_lp_balance = {
         let res: BalanceResponse = deps.querier.query_wasm_smart(
@@ -184,56 +922,322 @@ fn withdraw(
         res.balance
     };
 
+    // held LP tokens are already in the proxy, only the remainder is unbonded
+    let held_amount = HELD_LP.load(deps.storage)?.min(amount);
+    let unbond_amount = amount - held_amount;
+    HELD_LP.update(deps.storage, |held| -> StdResult<_> {
+        Ok(held.checked_sub(held_amount)?)
+    })?;
+
+    // Local accounting must never block a withdrawal, drift is reported by the Health query
+    BONDED.update(deps.storage, |bonded| -> StdResult<_> {
+        Ok(bonded.saturating_sub(unbond_amount))
+    })?;
+
+    // claim rewards so the generator sees a fresh reward balance
+    if claim_rewards {
+        response.messages.push(SubMsg::new(lp_staking::withdraw_msg(
+            &cfg.reward_contract_addr,
+        )?));
+    }
+
+    if !held_amount.is_zero() {
+        response.messages.push(SubMsg::new(WasmMsg::Execute {
+            contract_addr: cfg.lp_token_addr.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: account.clone(),
+                amount: held_amount,
+            })?,
+        }));
+        response = response.add_attribute("held_amount", held_amount);
+    }
+    if unbond_amount.is_zero() {
+        return Ok(response.add_attributes(vec![
+            attr("action", "withdraw"),
+            attr("account", account),
+            attr("amount", amount),
+        ]));
+    }
+
     // withdraw from the end reward contract
-    response.messages.push(SubMsg::new(WasmMsg::Execute {
-        contract_addr: cfg.reward_contract_addr.to_string(),
-        funds: vec![],
-        msg: to_binary(&VkrExecuteMsg::Unbond { amount })?,
-    }));
-
-    // Callback function
-    response.messages.push(SubMsg::new(WasmMsg::Execute {
-        contract_addr: env.contract.address.to_string(),
-        funds: vec![],
-        msg: to_binary(&ExecuteMsg::Callback(
-            CallbackMsg::TransferLpTokensAfterWithdraw {
-                account: deps.api.addr_validate(&account)?,
-                prev_lp_balance,
-            },
-        ))?,
-    }));
+    let unbond_msg = lp_staking::unbond_msg(&cfg.reward_contract_addr, unbond_amount)?;
+
+    // LP tokens are paid out by ClaimUnbonded once the unbonding period is over
+    let unbonding_period = SETTINGS.load(deps.storage)?.unbonding_period;
+    if unbonding_period > 0 {
+        response.messages.push(SubMsg::new(unbond_msg));
+
+        let account = deps.api.addr_validate(&account)?;
+        let release_time = env.block.time.plus_seconds(unbonding_period).seconds();
+
+        let mut withdrawals = PENDING_WITHDRAWALS
+            .may_load(deps.storage, &account)?
+            .unwrap_or_default();
+        withdrawals.push(PendingWithdrawal {
+            amount: unbond_amount,
+            release_time,
+        });
+        PENDING_WITHDRAWALS.save(deps.storage, &account, &withdrawals)?;
+        RESERVED_LP.update(deps.storage, |reserved| -> StdResult<_> {
+            Ok(reserved.checked_add(unbond_amount)?)
+        })?;
+
+        return Ok(response.add_attributes(vec![
+            attr("action", "queue_withdrawal"),
+            attr("account", account),
+            attr("amount", unbond_amount),
+            attr("release_time", release_time.to_string()),
+        ]));
+    }
+
+    // the unbonded LP tokens are transferred to the user once the staking contract replies,
+    // the held LP tokens leave the proxy before the unbond
+    WITHDRAW_CONTEXT.save(
+        deps.storage,
+        &WithdrawContext {
+            account: deps.api.addr_validate(&account)?,
+            amount: unbond_amount,
+            prev_lp_balance: prev_lp_balance
+                .checked_sub(held_amount)
+                .map_err(StdError::from)?,
+        },
+    )?;
+    response
+        .messages
+        .push(SubMsg::reply_on_success(unbond_msg, WITHDRAW_REPLY_ID));
 
     Ok(response)
 }
 
-pub fn transfer_lp_tokens_after_withdraw(
+/// @dev Transfers the LP tokens of every matured queued withdrawal. Fails while the proxy holds
+/// fewer LP tokens than owed, e.g. before the staking contract released the unbonded LP tokens
+/// @param account : User whose queued withdrawals are paid out
+fn claim_unbonded(deps: DepsMut, env: Env, account: String) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let account = deps.api.addr_validate(&account)?;
+
+    let (matured, pending): (Vec<_>, Vec<_>) = PENDING_WITHDRAWALS
+        .may_load(deps.storage, &account)?
+        .unwrap_or_default()
+        .into_iter()
+        .partition(|withdrawal| withdrawal.release_time <= env.block.time.seconds());
+
+    let amount: Uint128 = matured.iter().map(|withdrawal| withdrawal.amount).sum();
+    if amount.is_zero() {
+        return Err(ContractError::NoMaturedWithdrawals {});
+    }
+    let balance = query_token_balance(deps.as_ref(), &env, &cfg.lp_token_addr)?;
+    if balance < amount {
+        return Err(ContractError::InsufficientLpBalance {
+            expected: amount,
+            balance,
+        });
+    }
+
+    if pending.is_empty() {
+        PENDING_WITHDRAWALS.remove(deps.storage, &account);
+    } else {
+        PENDING_WITHDRAWALS.save(deps.storage, &account, &pending)?;
+    }
+    RESERVED_LP.update(deps.storage, |reserved| -> StdResult<_> {
+        Ok(reserved.checked_sub(amount)?)
+    })?;
+
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: cfg.lp_token_addr.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: account.to_string(),
+                amount,
+            })?,
+        })
+        .add_attributes(vec![
+            attr("action", "claim_unbonded"),
+            attr("account", account),
+            attr("amount", amount),
+        ]))
+}
+
+/// @dev Moves the bonded position to a new VKR LP staking contract. Can only be called by the owner.
+/// Pending rewards are claimed and the whole position is unbonded from the current staking contract,
+/// the unbond reply bonds the received LP tokens into the new one
+/// @param new_reward_contract : The VKR LP staking contract the position is moved to
+fn migrate_staking_contract(
     deps: DepsMut,
     env: Env,
-    account: Addr,
-    prev_lp_balance: Uint128,
+    info: MessageInfo,
+    new_reward_contract: String,
 ) -> Result<Response, ContractError> {
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    assert_active(deps.as_ref())?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let new_reward_contract = deps.api.addr_validate(&new_reward_contract)?;
+    if new_reward_contract == cfg.reward_contract_addr {
+        return Err(ContractError::SameStakingContract {});
+    }
+    let new_cfg = Config {
+        reward_contract_addr: new_reward_contract.clone(),
+        ..cfg.clone()
+    };
+    validate_staking_contract(deps.as_ref(), &new_cfg)?;
+
+    let bond_amount = query_staker_info(deps.as_ref(), &env, &cfg)?.bond_amount;
+    CONFIG.save(deps.storage, &new_cfg)?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "migrate_staking_contract"),
+        attr("old_reward_contract", &cfg.reward_contract_addr),
+        attr("new_reward_contract", &new_reward_contract),
+        attr("amount", bond_amount),
+    ]);
+    if bond_amount.is_zero() {
+        return Ok(response);
+    }
+
+    let prev_lp_balance = query_token_balance(deps.as_ref(), &env, &cfg.lp_token_addr)?;
+    STAKING_MIGRATION_CONTEXT.save(
+        deps.storage,
+        &StakingMigrationContext {
+            new_reward_contract,
+            prev_lp_balance,
+            amount: Uint128::zero(),
+        },
+    )?;
+
+    Ok(response
+        .add_message(lp_staking::withdraw_msg(&cfg.reward_contract_addr)?)
+        .add_submessage(SubMsg::reply_on_success(
+            lp_staking::unbond_msg(&cfg.reward_contract_addr, bond_amount)?,
+            MIGRATION_UNBOND_REPLY_ID,
+        )))
+}
+
+/// @dev Bonds the LP tokens unbonded from the old VKR LP staking contract into the new one
+fn bond_into_new_staking_contract(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let mut context = STAKING_MIGRATION_CONTEXT.load(deps.storage)?;
+
+    let lp_balance = query_token_balance(deps.as_ref(), &env, &cfg.lp_token_addr)?;
+    context.amount = lp_balance
+        .checked_sub(context.prev_lp_balance)
+        .map_err(StdError::from)?;
+    STAKING_MIGRATION_CONTEXT.save(deps.storage, &context)?;
+
+    Ok(Response::new().add_submessage(SubMsg::reply_on_success(
+        bond_msg(&cfg, context.amount)?,
+        MIGRATION_BOND_REPLY_ID,
+    )))
+}
+
+/// @dev Checks that the new VKR LP staking contract holds the whole migrated position
+fn finish_staking_migration(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let context = STAKING_MIGRATION_CONTEXT.load(deps.storage)?;
+
+    let bond_amount = query_staker_info(deps.as_ref(), &env, &cfg)?.bond_amount;
+    if bond_amount < context.amount {
+        return Err(ContractError::StakingMigrationMismatch {
+            expected: context.amount,
+            actual: bond_amount,
+        });
+    }
+    STAKING_MIGRATION_CONTEXT.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "finish_staking_migration"),
+        attr("reward_contract", context.new_reward_contract),
+        attr("amount", context.amount),
+    ]))
+}
+
+/// @dev Winds the proxy down. Can only be called by the owner.
+/// Claims the final rewards and unbonds the whole position into the proxy, where it is reserved
+/// for withdrawals. The bonded surplus is not part of any deposit and is not reserved.
+/// Deposits are disabled afterwards
+fn deprecate(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    assert_active(deps.as_ref())?;
+
     let cfg = CONFIG.load(deps.storage)?;
+    let bond_amount = query_staker_info(deps.as_ref(), &env, &cfg)?.bond_amount;
+    let held_amount = HELD_LP.load(deps.storage)?;
+    let surplus_amount = SURPLUS_BONDED.load(deps.storage)?;
+
+    STATUS.save(deps.storage, &ProxyStatus::Deprecated)?;
+    BONDED.save(deps.storage, &Uint128::zero())?;
+    HELD_LP.save(deps.storage, &Uint128::zero())?;
+    SURPLUS_BONDED.save(deps.storage, &Uint128::zero())?;
+    RESERVED_LP.update(deps.storage, |reserved| -> StdResult<_> {
+        Ok(reserved
+            .checked_add(bond_amount.saturating_sub(surplus_amount))?
+            .checked_add(held_amount)?)
+    })?;
+
+    let mut response = Response::new()
+        .add_message(lp_staking::withdraw_msg(&cfg.reward_contract_addr)?)
+        .add_attributes(vec![
+            attr("action", "deprecate"),
+            attr("amount", bond_amount),
+        ]);
+    if !bond_amount.is_zero() {
+        response = response.add_message(lp_staking::unbond_msg(
+            &cfg.reward_contract_addr,
+            bond_amount,
+        )?);
+    }
+    Ok(response)
+}
+
+/// @dev Transfers the LP tokens unbonded by the VKR LP staking contract to the user of the
+/// withdrawal waiting for the reply. Fails unless at least the requested amount arrived, any excess
+/// is not part of a deposit and is retained by the proxy as surplus, which RestakeIdle bonds
+fn transfer_lp_tokens_after_withdraw(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let WithdrawContext {
+        account,
+        amount,
+        prev_lp_balance,
+    } = WITHDRAW_CONTEXT.load(deps.storage)?;
+    WITHDRAW_CONTEXT.remove(deps.storage);
 
     // Calculate number of LP Tokens withdrawn from the staking contract
-    let amount = {
-        let res: BalanceResponse = deps.querier.query_wasm_smart(
-            &cfg.lp_token_addr,
-            &Cw20QueryMsg::Balance {
-                address: env.contract.address.to_string(),
-            },
-        )?;
-        res.balance - prev_lp_balance
-    };
+    let received = query_token_balance(deps.as_ref(), &env, &cfg.lp_token_addr)?
+        .checked_sub(prev_lp_balance)
+        .map_err(StdError::from)?;
+    if received < amount {
+        return Err(ContractError::WithdrawAmountMismatch {
+            expected: amount,
+            received,
+        });
+    }
 
-    Ok(Response::new().add_message(WasmMsg::Execute {
-        contract_addr: cfg.lp_token_addr.to_string(),
-        funds: vec![],
-        msg: to_binary(&Cw20ExecuteMsg::Transfer {
-            recipient: account.to_string(),
-            amount,
-        })?,
-    }))
+    let mut response = Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: cfg.lp_token_addr.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: account.to_string(),
+                amount,
+            })?,
+        })
+        .add_attributes(vec![
+            attr("action", "withdraw"),
+            attr("account", account),
+            attr("amount", amount),
+        ]);
+    if received > amount {
+        response = response.add_attribute("retained_excess", received - amount);
+    }
+    Ok(response)
 }
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     let cfg = CONFIG.load(deps.storage)?;
@@ -244,46 +1248,439 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             lp_token_addr: cfg.lp_token_addr.to_string(),
             reward_contract_addr: cfg.reward_contract_addr.to_string(),
             reward_token_addr: cfg.reward_token_addr.to_string(),
+            owner: OWNER.load(deps.storage)?.to_string(),
         }),
-        QueryMsg::Deposit {} => {
-            let res: StakerInfoResponse = deps.querier.query_wasm_smart(
-                cfg.reward_contract_addr,
-                &VkrQueryMsg::StakerInfo {
-                    staker: env.contract.address.to_string(),
-                },
-            )?;
-            let deposit_amount = res.bond_amount;
-            to_binary(&deposit_amount)
-        }
+        QueryMsg::Deposit {} => to_binary(&query_deposit(deps, &env, &cfg)?),
         QueryMsg::Reward {} => {
-            let res: Result<BalanceResponse, StdError> = deps.querier.query_wasm_smart(
-                cfg.reward_token_addr,
-                &Cw20QueryMsg::Balance {
-                    address: env.contract.address.into_string(),
-                },
-            );
-            let reward_amount = res?.balance;
+            let reward_amount = query_released_rewards(deps, &env, &cfg)?;
 
             to_binary(&reward_amount)
         }
         QueryMsg::PendingToken {} => {
-            let res: StakerInfoResponse = deps.querier.query_wasm_smart(
-                cfg.reward_contract_addr,
-                &VkrQueryMsg::StakerInfo {
-                    staker: env.contract.address.to_string(),
-                },
-            )?;
-            let pending_reward = res.pending_reward;
+            let pending_reward = query_pending_rewards(deps, &env, &cfg)?;
             to_binary(&Some(pending_reward))
         }
-        QueryMsg::RewardInfo {} => {
-            let config = CONFIG.load(deps.storage)?;
-            to_binary(&config.reward_token_addr)
+        QueryMsg::RewardInfo {} => to_binary(&query_distributed_token(deps, &cfg)?),
+        QueryMsg::HealthCheck {} => to_binary(&query_health_check(deps, &cfg)?),
+        QueryMsg::Health {} => to_binary(&query_health(deps, &env, &cfg)?),
+        QueryMsg::IdleBalance {} => to_binary(&query_idle_balance(deps, &env, &cfg)?),
+        QueryMsg::Settings {} => {
+            let settings = SETTINGS.load(deps.storage)?;
+            to_binary(&SettingsResponse {
+                claim_rewards_on_withdraw: settings.claim_rewards_on_withdraw,
+                unbonding_period: settings.unbonding_period,
+                hold_lp_on_bond_failure: settings.hold_lp_on_bond_failure,
+                harvest_interval: settings.harvest_interval,
+                harvest_bounty: settings.harvest_bounty,
+                min_claim_amount: settings.min_claim_amount,
+                reward_stream_period: settings.reward_stream_period,
+            })
+        }
+        QueryMsg::PendingWithdrawals { account } => {
+            to_binary(&query_pending_withdrawals(deps, &env, account)?)
+        }
+        QueryMsg::V2(msg) => query_v2(deps, env, cfg, msg),
+        QueryMsg::Status {} => to_binary(&STATUS.load(deps.storage)?),
+        QueryMsg::HarvestInfo {} => to_binary(&query_harvest_info(deps)?),
+        QueryMsg::StreamState {} => {
+            let reward_balance = query_generator_balance(deps, &env, &cfg)?;
+            to_binary(&query_stream_state(deps, &env, reward_balance)?)
         }
+        QueryMsg::RewardConversion {} => {
+            let conversion = REWARD_CONVERSION.may_load(deps.storage)?;
+            to_binary(&conversion.map(|conversion| RewardConversionResponse {
+                router: conversion.router,
+                operations: conversion.operations,
+                target_token: conversion.target_token,
+                max_spread: conversion.max_spread,
+                belief_price: conversion.belief_price,
+            }))
+        }
+        QueryMsg::RewardSplits {} => to_binary(&query_reward_splits(deps, &env, &cfg)?),
+        QueryMsg::AccruedSplit { recipient } => {
+            let recipient = deps.api.addr_validate(&recipient)?;
+            to_binary(&query_accrued_split(deps, &env, &cfg, &recipient)?)
+        }
+    }
+}
+
+/// @dev Answers the typed versions of the generator queries
+fn query_v2(deps: Deps, env: Env, cfg: Config, msg: QueryMsgV2) -> StdResult<Binary> {
+    match msg {
+        QueryMsgV2::Deposit {} => to_binary(&DepositResponse {
+            amount: query_deposit(deps, &env, &cfg)?,
+            lp_token: cfg.lp_token_addr,
+        }),
+        QueryMsgV2::Reward {} => to_binary(&RewardResponse {
+            amount: query_released_rewards(deps, &env, &cfg)?,
+            token: query_distributed_token(deps, &cfg)?,
+        }),
+        QueryMsgV2::PendingToken {} => to_binary(&PendingTokenResponse {
+            amount: query_pending_rewards(deps, &env, &cfg)?,
+            token: query_distributed_token(deps, &cfg)?,
+        }),
+        QueryMsgV2::RewardInfo {} => to_binary(&RewardInfoResponse {
+            token: query_distributed_token(deps, &cfg)?,
+            reward_contract: cfg.reward_contract_addr,
+        }),
+    }
+}
+
+/// @dev Returns the proxy position in the VKR LP staking contract
+fn query_staker_info(deps: Deps, env: &Env, cfg: &Config) -> StdResult<StakerInfoResponse> {
+    lp_staking::query_staker_info(
+        &deps.querier,
+        &cfg.reward_contract_addr,
+        &env.contract.address,
+    )
+}
+
+/// @dev Returns the amount of LP tokens deposited through the proxy: the bonded position without
+/// the surplus bonded by RestakeIdle and the LP tokens held after a failed bond. Once deprecated, the LP tokens reserved for withdrawals but
+/// not owed to queued withdrawals
+fn query_deposit(deps: Deps, env: &Env, cfg: &Config) -> StdResult<Uint128> {
+    if STATUS.load(deps.storage)? == ProxyStatus::Deprecated {
+        let queued_amount = PENDING_WITHDRAWALS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (_, withdrawals) = item?;
+                Ok(withdrawals
+                    .iter()
+                    .map(|withdrawal| withdrawal.amount)
+                    .sum::<Uint128>())
+            })
+            .sum::<StdResult<Uint128>>()?;
+        return Ok(RESERVED_LP
+            .load(deps.storage)?
+            .saturating_sub(queued_amount));
+    }
+
+    let bond_amount = query_staker_info(deps, env, cfg)?
+        .bond_amount
+        .saturating_sub(SURPLUS_BONDED.load(deps.storage)?);
+    Ok(bond_amount.checked_add(HELD_LP.load(deps.storage)?)?)
+}
+
+/// @dev Returns the proxy balance of the given CW20 token
+fn query_token_balance(deps: Deps, env: &Env, token: &Addr) -> StdResult<Uint128> {
+    let res: BalanceResponse = deps.querier.query_wasm_smart(
+        token,
+        &Cw20QueryMsg::Balance {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+    Ok(res.balance)
+}
+
+/// @dev Returns the config of the VKR LP staking contract
+fn query_upstream_config(deps: Deps, cfg: &Config) -> StdResult<VkrConfigResponse> {
+    lp_staking::query_config(&deps.querier, &cfg.reward_contract_addr)
+}
+
+/// @dev Reports every divergence between the stored config and the VKR LP staking contract config
+fn query_health_check(deps: Deps, cfg: &Config) -> StdResult<HealthCheckResponse> {
+    let upstream = query_upstream_config(deps, cfg)?;
+
+    let divergences: Vec<ConfigDivergence> = [
+        ("reward_token_addr", &cfg.reward_token_addr, upstream.token),
+        ("lp_token_addr", &cfg.lp_token_addr, upstream.lp_token),
+        ("pair_addr", &cfg.pair_addr, upstream.pair),
+    ]
+    .into_iter()
+    .filter(|(_, local, upstream)| local.as_str() != upstream)
+    .map(|(field, local, upstream)| ConfigDivergence {
+        field: field.to_string(),
+        local: local.to_string(),
+        upstream,
+    })
+    .collect();
+
+    Ok(HealthCheckResponse {
+        healthy: divergences.is_empty(),
+        divergences,
+    })
+}
+
+/// @dev Returns the amount of LP tokens held by the proxy which can be restaked
+fn query_idle_balance(deps: Deps, env: &Env, cfg: &Config) -> StdResult<IdleBalanceResponse> {
+    let lp_balance = query_token_balance(deps, env, &cfg.lp_token_addr)?;
+    let reserved_amount = RESERVED_LP.load(deps.storage)?;
+    let idle_amount = lp_balance.saturating_sub(reserved_amount);
+    let held_amount = HELD_LP.load(deps.storage)?;
+
+    Ok(IdleBalanceResponse {
+        lp_balance,
+        reserved_amount,
+        idle_amount,
+        held_amount,
+        surplus_amount: idle_amount.saturating_sub(held_amount),
+    })
+}
+
+/// @dev Returns the reward token balance of the proxy left to the generator without the rewards not
+/// released yet
+fn query_released_rewards(deps: Deps, env: &Env, cfg: &Config) -> StdResult<Uint128> {
+    let reward_balance = query_generator_balance(deps, env, cfg)?;
+    let stream = query_stream_state(deps, env, reward_balance)?;
+    Ok(reward_balance
+        .saturating_sub(stream.locked_amount)
+        .saturating_sub(stream.unscheduled_amount))
+}
+
+/// @dev Returns the generator share of the pending rewards in the VKR LP staking contract and the
+/// claimed rewards not released yet. When rewards are converted, the pending and the not yet
+/// converted rewards are reported as the amount of the target token the router returns for them
+fn query_pending_rewards(deps: Deps, env: &Env, cfg: &Config) -> StdResult<Uint128> {
+    let mut pending_reward = query_staker_info(deps, env, cfg)?.pending_reward;
+    if let Some(conversion) = REWARD_CONVERSION.may_load(deps.storage)? {
+        let unconverted = pending_reward + query_token_balance(deps, env, &cfg.reward_token_addr)?;
+        pending_reward = if unconverted.is_zero() {
+            Uint128::zero()
+        } else {
+            router::query_simulate_swap(
+                &deps.querier,
+                &conversion.router,
+                unconverted,
+                conversion.operations,
+            )?
+        };
     }
+
+    let reward_balance = query_generator_balance(deps, env, cfg)?;
+    let stream = query_stream_state(deps, env, reward_balance)?;
+    Ok(pending_reward * query_generator_weight(deps, cfg)?
+        + stream.locked_amount
+        + stream.unscheduled_amount)
+}
+
+/// @dev Returns the distributed token balance of the proxy left to the generator once the received
+/// rewards are split among the beneficiaries
+fn query_generator_balance(deps: Deps, env: &Env, cfg: &Config) -> StdResult<Uint128> {
+    let distributed_token = query_distributed_token(deps, cfg)?;
+    let reward_balance = query_token_balance(deps, env, &distributed_token)?;
+    let accrued_amount = split_shares(deps, cfg, reward_balance)?
+        .into_iter()
+        .map(|(_, share)| share)
+        .sum::<Uint128>()
+        + SPLIT_STATE.load(deps.storage)?.accrued_amount;
+    Ok(reward_balance.saturating_sub(accrued_amount))
+}
+
+/// @dev Returns the share of the claimed rewards left to the generator
+fn query_generator_weight(deps: Deps, cfg: &Config) -> StdResult<Decimal> {
+    let splits = REWARD_SPLITS.load(deps.storage)?;
+    if splits.is_empty() {
+        return Ok(Decimal::one());
+    }
+    Ok(splits
+        .into_iter()
+        .find(|split| split.recipient == cfg.generator_contract_addr)
+        .map(|split| split.weight)
+        .unwrap_or_default())
+}
+
+/// @dev Returns the rewards accrued to the beneficiary, including its share of the rewards
+/// received since the last split
+fn query_accrued_split(
+    deps: Deps,
+    env: &Env,
+    cfg: &Config,
+    recipient: &Addr,
+) -> StdResult<Uint128> {
+    let distributed_token = query_distributed_token(deps, cfg)?;
+    let reward_balance = query_token_balance(deps, env, &distributed_token)?;
+    let share = split_shares(deps, cfg, reward_balance)?
+        .into_iter()
+        .find(|(split_recipient, _)| split_recipient == recipient)
+        .map(|(_, share)| share)
+        .unwrap_or_default();
+    Ok(SPLIT_ACCRUED
+        .may_load(deps.storage, recipient)?
+        .unwrap_or_default()
+        + share)
+}
+
+/// @dev Returns the beneficiaries claimed rewards are split among and their accrued rewards. The
+/// generator accrues nothing, its share is reported by the reward query
+fn query_reward_splits(deps: Deps, env: &Env, cfg: &Config) -> StdResult<Vec<RewardSplitResponse>> {
+    REWARD_SPLITS
+        .load(deps.storage)?
+        .into_iter()
+        .map(|split| {
+            Ok(RewardSplitResponse {
+                accrued_amount: query_accrued_split(deps, env, cfg, &split.recipient)?,
+                recipient: split.recipient,
+                weight: split.weight,
+            })
+        })
+        .collect()
+}
+
+/// @dev Returns the token distributed to the generator: the conversion target if rewards are
+/// converted, the reward token otherwise
+fn query_distributed_token(deps: Deps, cfg: &Config) -> StdResult<Addr> {
+    Ok(match REWARD_CONVERSION.may_load(deps.storage)? {
+        Some(conversion) => conversion.target_token,
+        None => cfg.reward_token_addr.clone(),
+    })
+}
+
+/// @dev Returns the rewards of the current stream and the claimed rewards not streamed yet, which
+/// are only tracked while streaming is enabled
+/// @param reward_balance : The reward token balance of the proxy
+fn query_stream_state(
+    deps: Deps,
+    env: &Env,
+    reward_balance: Uint128,
+) -> StdResult<StreamStateResponse> {
+    let stream_period = SETTINGS.load(deps.storage)?.reward_stream_period;
+    let stream = REWARD_STREAM.load(deps.storage)?;
+    let unscheduled_amount = if stream_period > 0 {
+        reward_balance.saturating_sub(stream.accounted_balance)
+    } else {
+        Uint128::zero()
+    };
+
+    Ok(StreamStateResponse {
+        stream_period,
+        start_time: stream.start_time,
+        end_time: stream.end_time,
+        locked_amount: stream.locked_at(env.block.time.seconds()),
+        unscheduled_amount,
+    })
+}
+
+/// @dev Returns when the proxy was last harvested and when it can be harvested again
+fn query_harvest_info(deps: Deps) -> StdResult<HarvestInfoResponse> {
+    let last_harvest = LAST_HARVEST.load(deps.storage)?;
+    let harvest_interval = SETTINGS.load(deps.storage)?.harvest_interval;
+
+    Ok(HarvestInfoResponse {
+        last_harvest,
+        next_harvest: last_harvest.saturating_add(harvest_interval),
+    })
+}
+
+/// @dev Returns the queued withdrawals of the given account
+fn query_pending_withdrawals(
+    deps: Deps,
+    env: &Env,
+    account: String,
+) -> StdResult<PendingWithdrawalsResponse> {
+    let account = deps.api.addr_validate(&account)?;
+    let withdrawals = PENDING_WITHDRAWALS
+        .may_load(deps.storage, &account)?
+        .unwrap_or_default();
+    let claimable_amount = withdrawals
+        .iter()
+        .filter(|withdrawal| withdrawal.release_time <= env.block.time.seconds())
+        .map(|withdrawal| withdrawal.amount)
+        .sum();
+
+    Ok(PendingWithdrawalsResponse {
+        withdrawals,
+        claimable_amount,
+    })
+}
+
+/// @dev Summarises the proxy position and lists every invariant that does not hold
+fn query_health(deps: Deps, env: &Env, cfg: &Config) -> StdResult<HealthResponse> {
+    let staker_info = query_staker_info(deps, env, cfg)?;
+    let local_bond_amount = BONDED.may_load(deps.storage)?.unwrap_or_default();
+    let surplus_bond_amount = SURPLUS_BONDED.may_load(deps.storage)?.unwrap_or_default();
+    let idle_balance = query_idle_balance(deps, env, cfg)?;
+    let reward_balance = query_token_balance(deps, env, &cfg.reward_token_addr)?;
+    let version = get_contract_version(deps.storage)?;
+    let status = STATUS.load(deps.storage)?;
+    // a deprecated proxy stops deposits and has claimed its final rewards
+    let paused = status == ProxyStatus::Deprecated;
+
+    let mut failed_invariants = vec![];
+    if staker_info.bond_amount != local_bond_amount.checked_add(surplus_bond_amount)? {
+        failed_invariants.push(INVARIANT_BOND_AMOUNT.to_string());
+    }
+    // LP tokens sent outside of the deposit flow are reported as surplus, only deposited LP tokens
+    // are expected to be bonded
+    if idle_balance.idle_amount > idle_balance.surplus_amount {
+        failed_invariants.push(INVARIANT_NO_IDLE_LP.to_string());
+    }
+    if !query_health_check(deps, cfg)?.healthy {
+        failed_invariants.push(INVARIANT_UPSTREAM_CONFIG.to_string());
+    }
+
+    Ok(HealthResponse {
+        upstream_bond_amount: staker_info.bond_amount,
+        local_bond_amount,
+        surplus_bond_amount,
+        idle_lp_balance: idle_balance.idle_amount,
+        surplus_lp_balance: idle_balance.surplus_amount,
+        reward_balance,
+        pending_reward: staker_info.pending_reward,
+        contract_name: version.contract,
+        contract_version: version.version,
+        status,
+        deposits_paused: paused,
+        reward_claims_paused: paused,
+        failed_invariants,
+    })
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    match msg.owner {
+        Some(owner) => OWNER.save(deps.storage, &deps.api.addr_validate(&owner)?)?,
+        None if OWNER.may_load(deps.storage)?.is_none() => {
+            return Err(StdError::generic_err(
+                "Owner must be specified when migrating from a version without an owner",
+            ))
+        }
+        None => {}
+    }
+
+    // Proxies deployed before local bond accounting start from the upstream position
+    if BONDED.may_load(deps.storage)?.is_none() {
+        let cfg = CONFIG.load(deps.storage)?;
+        let staker_info = query_staker_info(deps.as_ref(), &env, &cfg)?;
+        BONDED.save(deps.storage, &staker_info.bond_amount)?;
+    }
+
+    if SETTINGS.may_load(deps.storage)?.is_none() {
+        SETTINGS.save(deps.storage, &Settings::default())?;
+    }
+
+    if RESERVED_LP.may_load(deps.storage)?.is_none() {
+        RESERVED_LP.save(deps.storage, &Uint128::zero())?;
+    }
+
+    if STATUS.may_load(deps.storage)?.is_none() {
+        STATUS.save(deps.storage, &ProxyStatus::Active)?;
+    }
+
+    if HELD_LP.may_load(deps.storage)?.is_none() {
+        HELD_LP.save(deps.storage, &Uint128::zero())?;
+    }
+
+    if SURPLUS_BONDED.may_load(deps.storage)?.is_none() {
+        SURPLUS_BONDED.save(deps.storage, &Uint128::zero())?;
+    }
+
+    if LAST_HARVEST.may_load(deps.storage)?.is_none() {
+        LAST_HARVEST.save(deps.storage, &0)?;
+    }
+
+    if REWARD_STREAM.may_load(deps.storage)?.is_none() {
+        REWARD_STREAM.save(deps.storage, &RewardStream::default())?;
+    }
+
+    if REWARD_SPLITS.may_load(deps.storage)?.is_none() {
+        REWARD_SPLITS.save(deps.storage, &vec![])?;
+    }
+
+    if SPLIT_STATE.may_load(deps.storage)?.is_none() {
+        SPLIT_STATE.save(deps.storage, &SplitState::default())?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     Ok(Response::default())
 }
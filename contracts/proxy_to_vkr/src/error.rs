@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Decimal, StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -11,4 +11,70 @@ pub enum ContractError {
 
     #[error("Incorrect CW20 hook message variant!")]
     IncorrectCw20HookMessageVariant {},
+
+    #[error("Reward token mismatch: expected {expected}, staking contract uses {actual}")]
+    RewardTokenMismatch { expected: String, actual: String },
+
+    #[error("LP token mismatch: expected {expected}, staking contract uses {actual}")]
+    LpTokenMismatch { expected: String, actual: String },
+
+    #[error("The proxy holds no idle LP tokens")]
+    NoIdleLpTokens {},
+
+    #[error("No queued withdrawals have matured yet")]
+    NoMaturedWithdrawals {},
+
+    #[error("Insufficient LP tokens: {expected} owed, the proxy holds {balance}")]
+    InsufficientLpBalance { expected: Uint128, balance: Uint128 },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("The proxy already uses this staking contract")]
+    SameStakingContract {},
+
+    #[error("Staking migration failed: expected {expected} LP tokens bonded, new staking contract reports {actual}")]
+    StakingMigrationMismatch { expected: Uint128, actual: Uint128 },
+
+    #[error("The proxy is deprecated")]
+    ProxyDeprecated {},
+
+    #[error("Withdraw amount mismatch: expected {expected} LP tokens, received {received}")]
+    WithdrawAmountMismatch {
+        expected: Uint128,
+        received: Uint128,
+    },
+
+    #[error("The proxy can not be harvested before {next_harvest}")]
+    HarvestTooSoon { next_harvest: u64 },
+
+    #[error("The harvest bounty can not exceed {max}")]
+    HarvestBountyTooHigh { max: Decimal },
+
+    #[error("A harvest bounty requires a non-zero harvest interval")]
+    HarvestIntervalRequired {},
+
+    #[error("The conversion route must contain at least one asset")]
+    EmptyConversionRoute {},
+
+    #[error("Rewards can only be converted to a CW20 token")]
+    NativeConversionTarget {},
+
+    #[error("The conversion max spread can not exceed {max}")]
+    ConversionSpreadTooHigh { max: Decimal },
+
+    #[error("The conversion belief price must be greater than zero")]
+    ZeroBeliefPrice {},
+
+    #[error("The distributed token can only change while the proxy holds no deposits")]
+    DistributedTokenLocked {},
+
+    #[error("The split weights must be positive and sum to one")]
+    InvalidSplitWeights {},
+
+    #[error("Duplicate split recipient: {recipient}")]
+    DuplicateSplitRecipient { recipient: String },
+
+    #[error("No rewards accrued to {recipient}")]
+    NoAccruedRewards { recipient: String },
 }
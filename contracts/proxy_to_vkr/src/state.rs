@@ -1,7 +1,9 @@
 use cosmwasm_schema::cw_serde;
 
-use cosmwasm_std::Addr;
-use cw_storage_plus::Item;
+use ap_valkyrie::router::SwapOperation;
+use ap_valkyrie::{PendingWithdrawal, ProxyStatus};
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw_storage_plus::{Item, Map};
 
 #[cw_serde]
 pub struct Config {
@@ -13,3 +15,175 @@ pub struct Config {
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Optional proxy behaviour configured by the owner
+#[cw_serde]
+#[derive(Default)]
+pub struct Settings {
+    /// Whether rewards are claimed from the VKR LP staking contract before LP tokens are unbonded
+    #[serde(default)]
+    pub claim_rewards_on_withdraw: bool,
+    /// Seconds withdrawn LP tokens are queued for before they can be claimed, zero pays them out instantly
+    #[serde(default)]
+    pub unbonding_period: u64,
+    /// Whether deposited LP tokens are held by the proxy when the VKR LP staking contract rejects the
+    /// bond, instead of reverting the deposit
+    #[serde(default)]
+    pub hold_lp_on_bond_failure: bool,
+    /// Minimum number of seconds between two harvests
+    #[serde(default)]
+    pub harvest_interval: u64,
+    /// Share of the harvested rewards paid to the caller of Harvest
+    #[serde(default)]
+    pub harvest_bounty: Decimal,
+    /// Pending rewards below this amount are not claimed by UpdateRewards, nothing is claimed when
+    /// no rewards are pending
+    #[serde(default)]
+    pub min_claim_amount: Uint128,
+    /// Seconds claimed rewards are released over, zero releases them at once
+    #[serde(default)]
+    pub reward_stream_period: u64,
+}
+
+pub const SETTINGS: Item<Settings> = Item::new("settings");
+
+/// Whether the proxy is active or deprecated
+pub const STATUS: Item<ProxyStatus> = Item::new("status");
+
+/// The contract owner
+pub const OWNER: Item<Addr> = Item::new("owner");
+
+/// Amount of LP tokens the proxy has bonded in the VKR LP staking contract by its own accounting
+pub const BONDED: Item<Uint128> = Item::new("bonded");
+
+/// Amount of LP tokens bonded by RestakeIdle that were sent to the proxy outside of the deposit
+/// flow, e.g. donations. They are not part of any deposit
+pub const SURPLUS_BONDED: Item<Uint128> = Item::new("surplus_bonded");
+
+/// Amount of LP tokens reserved for queued withdrawals and, once deprecated, for withdrawals
+pub const RESERVED_LP: Item<Uint128> = Item::new("reserved_lp");
+
+/// Amount of deposited LP tokens held by the proxy after a failed bond, until they are restaked
+pub const HELD_LP: Item<Uint128> = Item::new("held_lp");
+
+/// Amount of LP tokens of the deposit waiting for the reply of the bond submessage
+pub const PENDING_BOND: Item<Uint128> = Item::new("pending_bond");
+
+/// Timestamp in seconds of the last harvest
+pub const LAST_HARVEST: Item<u64> = Item::new("last_harvest");
+
+/// Claimed rewards released linearly between the start and the end time
+#[cw_serde]
+#[derive(Default)]
+pub struct RewardStream {
+    /// Timestamp in seconds the stream started at
+    pub start_time: u64,
+    /// Timestamp in seconds the stream releases its last rewards at
+    pub end_time: u64,
+    /// Amount of rewards locked at the start time
+    pub locked_amount: Uint128,
+    /// Reward token balance of the proxy accounted for by the stream
+    pub accounted_balance: Uint128,
+}
+
+impl RewardStream {
+    /// Returns the amount of rewards still locked at the given time
+    pub fn locked_at(&self, time: u64) -> Uint128 {
+        if time >= self.end_time {
+            return Uint128::zero();
+        }
+        self.locked_amount.multiply_ratio(
+            self.end_time - time.max(self.start_time),
+            self.end_time - self.start_time,
+        )
+    }
+}
+
+pub const REWARD_STREAM: Item<RewardStream> = Item::new("reward_stream");
+
+/// Conversion of claimed rewards through the Astroport router
+#[cw_serde]
+pub struct RewardConversion {
+    /// The Astroport router contract address
+    pub router: Addr,
+    /// The swaps converting the reward token to the target token
+    pub operations: Vec<SwapOperation>,
+    /// The CW20 token distributed instead of the reward token
+    pub target_token: Addr,
+    /// Maximum spread of every swap
+    pub max_spread: Decimal,
+    /// Expected amount of the target token per reward token the minimum received is derived from
+    #[serde(default)]
+    pub belief_price: Option<Decimal>,
+}
+
+/// Rewards are distributed as claimed unless a conversion is set
+pub const REWARD_CONVERSION: Item<RewardConversion> = Item::new("reward_conversion");
+
+/// Beneficiary of the claimed rewards
+#[cw_serde]
+pub struct RewardSplit {
+    /// The beneficiary address, the generator keeps its share for distribution
+    pub recipient: Addr,
+    /// Share of the claimed rewards accrued to the beneficiary
+    pub weight: Decimal,
+}
+
+/// Claimed rewards are left to the generator unless splits are set
+pub const REWARD_SPLITS: Item<Vec<RewardSplit>> = Item::new("reward_splits");
+
+/// Accounting of the rewards split among the beneficiaries
+#[cw_serde]
+#[derive(Default)]
+pub struct SplitState {
+    /// Distributed token balance of the proxy already split among the beneficiaries
+    pub accounted_balance: Uint128,
+    /// Amount of the distributed token accrued to the beneficiaries and not claimed yet
+    pub accrued_amount: Uint128,
+}
+
+pub const SPLIT_STATE: Item<SplitState> = Item::new("split_state");
+
+/// Amount of the distributed token accrued to every beneficiary and not claimed yet
+pub const SPLIT_ACCRUED: Map<&Addr, Uint128> = Map::new("split_accrued");
+
+/// Harvest waiting for the reply of the reward claim submessage
+#[cw_serde]
+pub struct HarvestContext {
+    /// Caller of Harvest receiving the bounty
+    pub keeper: Addr,
+    /// Reward token balance of the proxy before the claim
+    pub prev_reward_balance: Uint128,
+}
+
+pub const HARVEST_CONTEXT: Item<HarvestContext> = Item::new("harvest_context");
+
+/// Withdrawal waiting for the reply of the unbond submessage
+#[cw_serde]
+pub struct WithdrawContext {
+    /// User to which the unbonded LP tokens are transferred
+    pub account: Addr,
+    /// Number of LP tokens unbonded
+    pub amount: Uint128,
+    /// LP token balance of the proxy before unbonding
+    pub prev_lp_balance: Uint128,
+}
+
+pub const WITHDRAW_CONTEXT: Item<WithdrawContext> = Item::new("withdraw_context");
+
+/// Migration of the bonded position waiting for the replies of the unbond and bond submessages
+#[cw_serde]
+pub struct StakingMigrationContext {
+    /// The Valkyrie LP staking contract the position is moved to
+    pub new_reward_contract: Addr,
+    /// LP token balance of the proxy before unbonding
+    pub prev_lp_balance: Uint128,
+    /// Number of LP tokens bonded into the new staking contract, set once unbonded
+    pub amount: Uint128,
+}
+
+pub const STAKING_MIGRATION_CONTEXT: Item<StakingMigrationContext> =
+    Item::new("staking_migration_context");
+
+/// Queued withdrawals waiting for the unbonding period to end, per account
+pub const PENDING_WITHDRAWALS: Map<&Addr, Vec<PendingWithdrawal>> = Map::new("pending_withdrawals");
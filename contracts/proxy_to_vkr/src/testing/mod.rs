@@ -1,2 +1 @@
-mod mock_querier;
 mod tests;
@@ -1,14 +1,33 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{
+    execute, instantiate, query, reply, BOND_REPLY_ID, CONVERT_REPLY_ID, HARVEST_REPLY_ID,
+    MIGRATION_BOND_REPLY_ID, MIGRATION_UNBOND_REPLY_ID, STREAM_REPLY_ID, SWAP_REPLY_ID,
+    WITHDRAW_REPLY_ID,
+};
 use crate::error::ContractError;
-use crate::state::{Config, CONFIG};
-use crate::testing::mock_querier::mock_dependencies;
-use astroport::generator_proxy::{CallbackMsg, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::state::{
+    Config, WithdrawContext, BONDED, CONFIG, HELD_LP, OWNER, PENDING_BOND, PENDING_WITHDRAWALS,
+    RESERVED_LP, STAKING_MIGRATION_CONTEXT, SURPLUS_BONDED, WITHDRAW_CONTEXT,
+};
+use ap_valkyrie::lp_staking::{Cw20HookMsg as VkrCw20HookMsg, ExecuteMsg as VkrExecuteMsg};
+use ap_valkyrie::router::{AssetInfo, Cw20HookMsg as RouterCw20HookMsg, SwapOperation};
+use ap_valkyrie::{
+    ConfigDivergence, Cw20HookMsg, DepositResponse, ExecuteMsg, HarvestInfoResponse,
+    HealthCheckResponse, HealthResponse, IdleBalanceResponse, InstantiateMsg, PendingTokenResponse,
+    PendingWithdrawal, PendingWithdrawalsResponse, ProxyStatus, QueryMsg, QueryMsgV2,
+    RewardConversionInfo, RewardConversionResponse, RewardInfoResponse, RewardResponse,
+    RewardSplitInfo, RewardSplitResponse, SettingsResponse, StreamStateResponse,
+    INVARIANT_BOND_AMOUNT, INVARIANT_NO_IDLE_LP,
+};
+use ap_valkyrie_testing::{
+    mock_dependencies, ProxyInstantiateMsgBuilder, StakingConfig, GENERATOR, LP_TOKEN, PAIR,
+    REWARD_CONTRACT, REWARD_TOKEN,
+};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
-use cosmwasm_std::{from_binary, to_binary, Addr, CosmosMsg, SubMsg, Uint128, WasmMsg};
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
-use valkyrie::lp_staking::execute_msgs::{
-    Cw20HookMsg as VkrCw20HookMsg, ExecuteMsg as VkrExecuteMsg,
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, Reply,
+    SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
 };
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
 #[test]
 fn test_proper_initialization() {
@@ -20,6 +39,7 @@ fn test_proper_initialization() {
         lp_token_addr: "vkrust0000".to_string(),
         reward_contract_addr: "reward0000".to_string(),
         reward_token_addr: "vkr0000".to_string(),
+        owner: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -31,6 +51,93 @@ fn test_proper_initialization() {
     assert_eq!("vkrust0000", config.lp_token_addr.as_str());
     assert_eq!("reward0000", config.reward_contract_addr.as_str());
     assert_eq!("vkr0000", config.reward_token_addr.as_str());
+    assert_eq!(
+        "addr0000",
+        OWNER.load(deps.as_ref().storage).unwrap().as_str()
+    );
+}
+
+#[test]
+fn test_initialization_validates_staking_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        generator_contract_addr: "generator0000".to_string(),
+        pair_addr: "pair0000".to_string(),
+        lp_token_addr: "vkrust0000".to_string(),
+        reward_contract_addr: "reward0000".to_string(),
+        reward_token_addr: "vkr0000".to_string(),
+        owner: None,
+    };
+    let info = mock_info("addr0000", &[]);
+
+    // the staking contract distributes another reward token
+    deps.querier
+        .with_staking_config("other0000", "pair0000", "vkrust0000");
+    let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RewardTokenMismatch {
+            expected: "vkr0000".to_string(),
+            actual: "other0000".to_string(),
+        }
+    );
+
+    // the staking contract accepts another LP token
+    deps.querier
+        .with_staking_config("vkr0000", "pair0000", "otherlp0000");
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::LpTokenMismatch {
+            expected: "vkrust0000".to_string(),
+            actual: "otherlp0000".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_health_check() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        generator_contract_addr: "generator0000".to_string(),
+        pair_addr: "pair0000".to_string(),
+        lp_token_addr: "vkrust0000".to_string(),
+        reward_contract_addr: "reward0000".to_string(),
+        reward_token_addr: "vkr0000".to_string(),
+        owner: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::HealthCheck {}).unwrap();
+    let query_res: HealthCheckResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        query_res,
+        HealthCheckResponse {
+            healthy: true,
+            divergences: vec![],
+        }
+    );
+
+    // the reward token was changed upstream after instantiation
+    deps.querier
+        .with_staking_config("vkr0001", "pair0000", "vkrust0000");
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::HealthCheck {}).unwrap();
+    let query_res: HealthCheckResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        query_res,
+        HealthCheckResponse {
+            healthy: false,
+            divergences: vec![ConfigDivergence {
+                field: "reward_token_addr".to_string(),
+                local: "vkr0000".to_string(),
+                upstream: "vkr0001".to_string(),
+            }],
+        }
+    );
 }
 
 #[test]
@@ -43,6 +150,7 @@ fn test_deposit() {
         lp_token_addr: "vkrust0000".to_string(),
         reward_contract_addr: "reward0000".to_string(),
         reward_token_addr: "vkr0000".to_string(),
+        owner: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -119,6 +227,7 @@ fn test_update_rewards() {
         lp_token_addr: "vkrust0000".to_string(),
         reward_contract_addr: "reward0000".to_string(),
         reward_token_addr: "vkr0000".to_string(),
+        owner: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -134,6 +243,8 @@ fn test_update_rewards() {
     .unwrap_err();
     assert_eq!(ContractError::Unauthorized {}, err);
 
+    deps.querier
+        .with_reward_info(Uint128::from(5u128), Uint128::from(100u128));
     let info = mock_info("generator0000", &[]);
     let res = execute(
         deps.as_mut(),
@@ -170,6 +281,87 @@ fn test_update_rewards() {
     assert_eq!(query_res, Uint128::from(0u128));
 }
 
+#[test]
+fn test_update_rewards_min_claim_amount() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = ProxyInstantiateMsgBuilder::new().owner("owner0000").build();
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let update_rewards = |deps: DepsMut| {
+        execute(
+            deps,
+            mock_env(),
+            mock_info(GENERATOR, &[]),
+            ExecuteMsg::UpdateRewards {},
+        )
+        .unwrap()
+    };
+
+    // nothing is claimed while no rewards are pending
+    let res = update_rewards(deps.as_mut());
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_rewards"),
+            attr("pending_reward", "0"),
+            attr("claim_skipped", "true"),
+        ]
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateSettings {
+            claim_rewards_on_withdraw: None,
+            unbonding_period: None,
+            hold_lp_on_bond_failure: None,
+            harvest_interval: None,
+            harvest_bounty: None,
+            min_claim_amount: Some(Uint128::new(100)),
+            reward_stream_period: None,
+        },
+    )
+    .unwrap();
+
+    // pending rewards below the threshold are left upstream
+    deps.querier
+        .ledger_mut()
+        .accrue(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(99));
+    let res = update_rewards(deps.as_mut());
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_rewards"),
+            attr("pending_reward", "99"),
+            attr("claim_skipped", "true"),
+        ]
+    );
+
+    // and claimed once they reach it
+    deps.querier
+        .ledger_mut()
+        .accrue(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(1));
+    let res = update_rewards(deps.as_mut());
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: REWARD_CONTRACT.to_string(),
+            funds: vec![],
+            msg: to_binary(&VkrExecuteMsg::Withdraw {}).unwrap(),
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_rewards"),
+            attr("pending_reward", "100"),
+        ]
+    );
+}
+
 #[test]
 fn test_send_rewards() {
     let mut deps = mock_dependencies(&[]);
@@ -180,6 +372,7 @@ fn test_send_rewards() {
         lp_token_addr: "vkrust0000".to_string(),
         reward_contract_addr: "reward0000".to_string(),
         reward_token_addr: "vkr0000".to_string(),
+        owner: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -230,6 +423,7 @@ fn test_withdraw() {
         lp_token_addr: "vkrust0000".to_string(),
         reward_contract_addr: "reward0000".to_string(),
         reward_token_addr: "vkr0000".to_string(),
+        owner: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -253,27 +447,25 @@ fn test_withdraw() {
 
     assert_eq!(
         res.messages,
-        vec![
-            SubMsg::new(WasmMsg::Execute {
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Execute {
                 contract_addr: "reward0000".to_string(),
                 funds: vec![],
                 msg: to_binary(&VkrExecuteMsg::Unbond {
                     amount: Uint128::new(100),
                 })
                 .unwrap(),
-            }),
-            SubMsg::new(WasmMsg::Execute {
-                contract_addr: "cosmos2contract".to_string(),
-                msg: to_binary(&ExecuteMsg::Callback(
-                    CallbackMsg::TransferLpTokensAfterWithdraw {
-                        account: Addr::unchecked("addr0000"),
-                        prev_lp_balance: Uint128::new(0),
-                    },
-                ))
-                .unwrap(),
-                funds: vec![]
-            })
-        ]
+            },
+            WITHDRAW_REPLY_ID
+        )]
+    );
+    assert_eq!(
+        WITHDRAW_CONTEXT.load(deps.as_ref().storage).unwrap(),
+        WithdrawContext {
+            account: Addr::unchecked("addr0000"),
+            amount: Uint128::new(100),
+            prev_lp_balance: Uint128::new(0),
+        }
     );
 }
 
@@ -292,6 +484,7 @@ fn test_emergency_withdraw() {
         lp_token_addr: "vkrust0000".to_string(),
         reward_contract_addr: "reward0000".to_string(),
         reward_token_addr: "vkr0000".to_string(),
+        owner: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -315,27 +508,25 @@ fn test_emergency_withdraw() {
 
     assert_eq!(
         res.messages,
-        vec![
-            SubMsg::new(WasmMsg::Execute {
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Execute {
                 contract_addr: "reward0000".to_string(),
                 funds: vec![],
                 msg: to_binary(&VkrExecuteMsg::Unbond {
                     amount: Uint128::new(100),
                 })
                 .unwrap(),
-            }),
-            SubMsg::new(WasmMsg::Execute {
-                contract_addr: "cosmos2contract".to_string(),
-                msg: to_binary(&ExecuteMsg::Callback(
-                    CallbackMsg::TransferLpTokensAfterWithdraw {
-                        account: Addr::unchecked("addr0000"),
-                        prev_lp_balance: Uint128::new(100),
-                    },
-                ))
-                .unwrap(),
-                funds: vec![]
-            })
-        ]
+            },
+            WITHDRAW_REPLY_ID
+        )]
+    );
+    assert_eq!(
+        WITHDRAW_CONTEXT.load(deps.as_ref().storage).unwrap(),
+        WithdrawContext {
+            account: Addr::unchecked("addr0000"),
+            amount: Uint128::new(100),
+            prev_lp_balance: Uint128::new(100),
+        }
     );
 }
 
@@ -349,6 +540,7 @@ fn test_query_reward_info() {
         lp_token_addr: "vkrust0000".to_string(),
         reward_contract_addr: "reward0000".to_string(),
         reward_token_addr: "vkr0000".to_string(),
+        owner: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -358,3 +550,2042 @@ fn test_query_reward_info() {
     let query_res: Addr = from_binary(&res).unwrap();
     assert_eq!(query_res, Addr::unchecked("vkr0000"));
 }
+
+#[test]
+fn test_health() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        generator_contract_addr: "generator0000".to_string(),
+        pair_addr: "pair0000".to_string(),
+        lp_token_addr: "vkrust0000".to_string(),
+        reward_contract_addr: "reward0000".to_string(),
+        reward_token_addr: "vkr0000".to_string(),
+        owner: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("vkrust0000", &[]);
+    let deposit_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "generator0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), info, deposit_msg).unwrap();
+
+    deps.querier.with_token_balances(&[
+        (
+            &"vkrust0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        ),
+        (
+            &"vkr0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(7u128))],
+        ),
+    ]);
+    deps.querier
+        .with_reward_info(Uint128::from(5u128), Uint128::from(100u128));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Health {}).unwrap();
+    let query_res: HealthResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        query_res,
+        HealthResponse {
+            upstream_bond_amount: Uint128::from(100u128),
+            local_bond_amount: Uint128::from(100u128),
+            surplus_bond_amount: Uint128::zero(),
+            idle_lp_balance: Uint128::zero(),
+            surplus_lp_balance: Uint128::zero(),
+            reward_balance: Uint128::from(7u128),
+            pending_reward: Uint128::from(5u128),
+            contract_name: "astroport-generator-proxy-to-vkr".to_string(),
+            contract_version: env!("CARGO_PKG_VERSION").to_string(),
+            status: ProxyStatus::Active,
+            deposits_paused: false,
+            reward_claims_paused: false,
+            failed_invariants: vec![],
+        }
+    );
+
+    // part of the position was unbonded without going through the proxy accounting
+    deps.querier.with_token_balances(&[
+        (
+            &"vkrust0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10u128))],
+        ),
+        (
+            &"vkr0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(7u128))],
+        ),
+    ]);
+    deps.querier
+        .with_reward_info(Uint128::from(5u128), Uint128::from(90u128));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Health {}).unwrap();
+    let query_res: HealthResponse = from_binary(&res).unwrap();
+    assert_eq!(query_res.upstream_bond_amount, Uint128::from(90u128));
+    assert_eq!(query_res.local_bond_amount, Uint128::from(100u128));
+    assert_eq!(query_res.idle_lp_balance, Uint128::from(10u128));
+    // LP tokens the proxy can't account for are surplus, not idle deposits
+    assert_eq!(query_res.surplus_lp_balance, Uint128::from(10u128));
+    assert_eq!(
+        query_res.failed_invariants,
+        vec![INVARIANT_BOND_AMOUNT.to_string()]
+    );
+
+    // deposited LP tokens held after a failed bond are not bonded upstream
+    HELD_LP
+        .save(deps.as_mut().storage, &Uint128::from(10u128))
+        .unwrap();
+    BONDED
+        .save(deps.as_mut().storage, &Uint128::from(90u128))
+        .unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Health {}).unwrap();
+    let query_res: HealthResponse = from_binary(&res).unwrap();
+    assert_eq!(query_res.idle_lp_balance, Uint128::from(10u128));
+    assert!(query_res.surplus_lp_balance.is_zero());
+    assert_eq!(
+        query_res.failed_invariants,
+        vec![INVARIANT_NO_IDLE_LP.to_string()]
+    );
+}
+
+#[test]
+fn test_restake_idle() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        generator_contract_addr: "generator0000".to_string(),
+        pair_addr: "pair0000".to_string(),
+        lp_token_addr: "vkrust0000".to_string(),
+        reward_contract_addr: "reward0000".to_string(),
+        reward_token_addr: "vkr0000".to_string(),
+        owner: Some("owner0000".to_string()),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // nothing to restake
+    deps.querier.with_token_balances(&[(
+        &"vkrust0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+    )]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RestakeIdle {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoIdleLpTokens {});
+
+    // LP tokens were donated to the proxy
+    deps.querier.with_token_balances(&[(
+        &"vkrust0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(30u128))],
+    )]);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::IdleBalance {}).unwrap();
+    let query_res: IdleBalanceResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        query_res,
+        IdleBalanceResponse {
+            lp_balance: Uint128::from(30u128),
+            reserved_amount: Uint128::zero(),
+            idle_amount: Uint128::from(30u128),
+            held_amount: Uint128::zero(),
+            surplus_amount: Uint128::from(30u128),
+        }
+    );
+
+    // fails when called from unauthorized
+    let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::RestakeIdle {}).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // deposited LP tokens held after failed bonds are bonded back into the deposit, the donated
+    // ones are bonded as surplus
+    HELD_LP
+        .save(deps.as_mut().storage, &Uint128::from(10u128))
+        .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RestakeIdle {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "vkrust0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "reward0000".to_string(),
+                amount: Uint128::from(30u128),
+                msg: to_binary(&VkrCw20HookMsg::Bond {}).unwrap(),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "restake_idle"),
+            attr("amount", "30"),
+            attr("held_amount", "10"),
+            attr("surplus_amount", "20"),
+        ]
+    );
+    assert_eq!(
+        BONDED.load(deps.as_ref().storage).unwrap(),
+        Uint128::from(10u128)
+    );
+    assert_eq!(
+        HELD_LP.load(deps.as_ref().storage).unwrap(),
+        Uint128::zero()
+    );
+    assert_eq!(
+        SURPLUS_BONDED.load(deps.as_ref().storage).unwrap(),
+        Uint128::from(20u128)
+    );
+
+    // the generator can restake as well
+    deps.querier.with_token_balances(&[(
+        &"vkrust0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(5u128))],
+    )]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("generator0000", &[]),
+        ExecuteMsg::RestakeIdle {},
+    )
+    .unwrap();
+    assert_eq!(
+        BONDED.load(deps.as_ref().storage).unwrap(),
+        Uint128::from(10u128)
+    );
+    assert_eq!(
+        SURPLUS_BONDED.load(deps.as_ref().storage).unwrap(),
+        Uint128::from(25u128)
+    );
+}
+
+#[test]
+fn test_withdraw_with_reward_claim() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[(
+        &String::from("vkrust0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(0))],
+    )]);
+
+    let msg = InstantiateMsg {
+        generator_contract_addr: "generator0000".to_string(),
+        pair_addr: "pair0000".to_string(),
+        lp_token_addr: "vkrust0000".to_string(),
+        reward_contract_addr: "reward0000".to_string(),
+        reward_token_addr: "vkr0000".to_string(),
+        owner: Some("owner0000".to_string()),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // only the owner can update settings
+    let update_settings_msg = ExecuteMsg::UpdateSettings {
+        claim_rewards_on_withdraw: Some(true),
+        unbonding_period: None,
+        hold_lp_on_bond_failure: None,
+        harvest_interval: None,
+        harvest_bounty: None,
+        min_claim_amount: None,
+        reward_stream_period: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, update_settings_msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        update_settings_msg,
+    )
+    .unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Settings {}).unwrap();
+    let query_res: SettingsResponse = from_binary(&res).unwrap();
+    assert!(query_res.claim_rewards_on_withdraw);
+
+    // rewards are claimed before the LP tokens are unbonded
+    let generator_info = mock_info("generator0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        generator_info.clone(),
+        ExecuteMsg::Withdraw {
+            account: "addr0000".to_string(),
+            amount: Uint128::new(100),
+        },
+    )
+    .unwrap();
+
+    let unbond_msg = SubMsg::reply_on_success(
+        WasmMsg::Execute {
+            contract_addr: "reward0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&VkrExecuteMsg::Unbond {
+                amount: Uint128::new(100),
+            })
+            .unwrap(),
+        },
+        WITHDRAW_REPLY_ID,
+    );
+
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "reward0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&VkrExecuteMsg::Withdraw {}).unwrap(),
+            }),
+            unbond_msg.clone(),
+        ]
+    );
+
+    // emergency withdrawals never claim rewards
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        generator_info,
+        ExecuteMsg::EmergencyWithdraw {
+            account: "addr0000".to_string(),
+            amount: Uint128::new(100),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![unbond_msg]);
+}
+
+#[test]
+fn test_queued_withdrawals() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        generator_contract_addr: "generator0000".to_string(),
+        pair_addr: "pair0000".to_string(),
+        lp_token_addr: "vkrust0000".to_string(),
+        reward_contract_addr: "reward0000".to_string(),
+        reward_token_addr: "vkr0000".to_string(),
+        owner: Some("owner0000".to_string()),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateSettings {
+            claim_rewards_on_withdraw: None,
+            unbonding_period: Some(100),
+            hold_lp_on_bond_failure: None,
+            harvest_interval: None,
+            harvest_bounty: None,
+            min_claim_amount: None,
+            reward_stream_period: None,
+        },
+    )
+    .unwrap();
+
+    // the withdrawal is queued instead of being paid out on the unbond reply
+    let env = mock_env();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("generator0000", &[]),
+        ExecuteMsg::Withdraw {
+            account: "addr0000".to_string(),
+            amount: Uint128::new(100),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "reward0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&VkrExecuteMsg::Unbond {
+                amount: Uint128::new(100),
+            })
+            .unwrap(),
+        })]
+    );
+
+    let release_time = env.block.time.seconds() + 100;
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::PendingWithdrawals {
+            account: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let query_res: PendingWithdrawalsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        query_res,
+        PendingWithdrawalsResponse {
+            withdrawals: vec![PendingWithdrawal {
+                amount: Uint128::new(100),
+                release_time,
+            }],
+            claimable_amount: Uint128::zero(),
+        }
+    );
+
+    // unbonded LP tokens are reserved and can't be restaked
+    deps.querier.with_token_balances(&[(
+        &"vkrust0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::IdleBalance {}).unwrap();
+    let query_res: IdleBalanceResponse = from_binary(&res).unwrap();
+    assert_eq!(query_res.reserved_amount, Uint128::new(100));
+    assert_eq!(query_res.idle_amount, Uint128::zero());
+
+    // nothing can be claimed before the unbonding period ends
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::ClaimUnbonded {
+            account: "addr0000".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoMaturedWithdrawals {});
+
+    // the owed LP tokens must have been released by the staking contract
+    let mut env = env;
+    env.block.time = env.block.time.plus_seconds(100);
+    deps.querier.with_token_balances(&[(
+        &"vkrust0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(60u128))],
+    )]);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::ClaimUnbonded {
+            account: "addr0000".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientLpBalance {
+            expected: Uint128::new(100),
+            balance: Uint128::new(60),
+        }
+    );
+
+    // anyone can pay out matured withdrawals to the account
+    deps.querier.with_token_balances(&[(
+        &"vkrust0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::ClaimUnbonded {
+            account: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "vkrust0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::new(100),
+            })
+            .unwrap(),
+        })]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::PendingWithdrawals {
+            account: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let query_res: PendingWithdrawalsResponse = from_binary(&res).unwrap();
+    assert!(query_res.withdrawals.is_empty());
+    assert_eq!(
+        RESERVED_LP.load(deps.as_ref().storage).unwrap(),
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn test_withdraw_reply_transfers_unbonded_amount() {
+    let mut deps = mock_dependencies(&[]);
+
+    // LP tokens donated to the proxy must not be paid out with a withdrawal
+    deps.querier
+        .ledger_mut()
+        .set_balance(LP_TOKEN, MOCK_CONTRACT_ADDR, Uint128::new(20));
+    deps.querier
+        .ledger_mut()
+        .bond(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(100));
+
+    let msg = ProxyInstantiateMsgBuilder::new().build();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("generator0000", &[]),
+        ExecuteMsg::Withdraw {
+            account: "addr0000".to_string(),
+            amount: Uint128::new(60),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, WITHDRAW_REPLY_ID);
+
+    // the staking contract processes the unbond message before the reply
+    deps.querier
+        .ledger_mut()
+        .unbond(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(60));
+
+    let res = reply(deps.as_mut(), mock_env(), reply_ok(WITHDRAW_REPLY_ID)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: LP_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::new(60),
+            })
+            .unwrap(),
+        })]
+    );
+    assert_eq!(
+        deps.querier
+            .ledger()
+            .staker(REWARD_CONTRACT, MOCK_CONTRACT_ADDR)
+            .bond_amount,
+        Uint128::new(40)
+    );
+    // the context is consumed by the reply
+    assert!(WITHDRAW_CONTEXT
+        .may_load(deps.as_ref().storage)
+        .unwrap()
+        .is_none());
+
+    // the staking contract must unbond at least the requested amount
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("generator0000", &[]),
+        ExecuteMsg::Withdraw {
+            account: "addr0000".to_string(),
+            amount: Uint128::new(20),
+        },
+    )
+    .unwrap();
+    deps.querier
+        .ledger_mut()
+        .unbond(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(20));
+    deps.querier
+        .ledger_mut()
+        .transfer(LP_TOKEN, MOCK_CONTRACT_ADDR, "fees0000", Uint128::new(1));
+    let err = reply(deps.as_mut(), mock_env(), reply_ok(WITHDRAW_REPLY_ID)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::WithdrawAmountMismatch {
+            expected: Uint128::new(20),
+            received: Uint128::new(19),
+        }
+    );
+
+    // any excess is retained by the proxy as surplus
+    let prev_lp_balance = deps.querier.ledger().balance(LP_TOKEN, MOCK_CONTRACT_ADDR);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::IdleBalance {}).unwrap();
+    let prev_surplus = from_binary::<IdleBalanceResponse>(&res)
+        .unwrap()
+        .surplus_amount;
+    WITHDRAW_CONTEXT
+        .save(
+            deps.as_mut().storage,
+            &WithdrawContext {
+                account: Addr::unchecked("addr0000"),
+                amount: Uint128::new(10),
+                prev_lp_balance,
+            },
+        )
+        .unwrap();
+    deps.querier
+        .ledger_mut()
+        .unbond(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(15));
+    let res = reply(deps.as_mut(), mock_env(), reply_ok(WITHDRAW_REPLY_ID)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: LP_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::new(10),
+            })
+            .unwrap(),
+        })]
+    );
+    assert!(res.attributes.contains(&attr("retained_excess", "5")));
+    deps.querier
+        .ledger_mut()
+        .transfer(LP_TOKEN, MOCK_CONTRACT_ADDR, "addr0000", Uint128::new(10));
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::IdleBalance {}).unwrap();
+    let idle_balance: IdleBalanceResponse = from_binary(&res).unwrap();
+    assert_eq!(idle_balance.surplus_amount, prev_surplus + Uint128::new(5));
+
+    // and bonded with the rest of the surplus by RestakeIdle
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("generator0000", &[]),
+        ExecuteMsg::RestakeIdle {},
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("surplus_amount", idle_balance.surplus_amount)));
+    assert_eq!(
+        SURPLUS_BONDED.load(deps.as_ref().storage).unwrap(),
+        idle_balance.surplus_amount
+    );
+
+    // unknown replies are rejected
+    let err = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 42,
+            ..reply_ok(WITHDRAW_REPLY_ID)
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::UnknownReplyId { id: 42 });
+}
+
+fn reply_ok(id: u64) -> Reply {
+    Reply {
+        id,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    }
+}
+
+#[test]
+fn test_typed_queries() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = ProxyInstantiateMsgBuilder::new().build();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .ledger_mut()
+        .set_balance("vkr0000", MOCK_CONTRACT_ADDR, Uint128::new(7));
+    deps.querier
+        .with_reward_info(Uint128::new(5), Uint128::new(100));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::V2(QueryMsgV2::Deposit {}),
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<DepositResponse>(&res).unwrap(),
+        DepositResponse {
+            amount: Uint128::new(100),
+            lp_token: Addr::unchecked("vkrust0000"),
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::V2(QueryMsgV2::Reward {}),
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<RewardResponse>(&res).unwrap(),
+        RewardResponse {
+            amount: Uint128::new(7),
+            token: Addr::unchecked("vkr0000"),
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::V2(QueryMsgV2::PendingToken {}),
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<PendingTokenResponse>(&res).unwrap(),
+        PendingTokenResponse {
+            amount: Uint128::new(5),
+            token: Addr::unchecked("vkr0000"),
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::V2(QueryMsgV2::RewardInfo {}),
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<RewardInfoResponse>(&res).unwrap(),
+        RewardInfoResponse {
+            token: Addr::unchecked("vkr0000"),
+            reward_contract: Addr::unchecked("reward0000"),
+        }
+    );
+
+    // the namespace is kept apart from the generator queries on the wire
+    assert_eq!(
+        to_binary(&QueryMsg::V2(QueryMsgV2::Deposit {})).unwrap(),
+        Binary::from(br#"{"v2":{"deposit":{}}}"#.as_slice())
+    );
+}
+
+#[test]
+fn test_migrate_staking_contract() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .ledger_mut()
+        .bond(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(100));
+    deps.querier.ledger_mut().set_staking_config(
+        "reward0001",
+        StakingConfig {
+            token: REWARD_TOKEN.to_string(),
+            pair: PAIR.to_string(),
+            lp_token: LP_TOKEN.to_string(),
+        },
+    );
+    deps.querier.ledger_mut().set_staking_config(
+        "reward0002",
+        StakingConfig {
+            token: "foreign0000".to_string(),
+            pair: PAIR.to_string(),
+            lp_token: LP_TOKEN.to_string(),
+        },
+    );
+
+    let msg = ProxyInstantiateMsgBuilder::new().owner("owner0000").build();
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let migrate_msg = |new_reward_contract: &str| ExecuteMsg::MigrateStakingContract {
+        new_reward_contract: new_reward_contract.to_string(),
+    };
+
+    // only the owner can move the position
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("generator0000", &[]),
+        migrate_msg("reward0001"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        migrate_msg(REWARD_CONTRACT),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::SameStakingContract {});
+
+    // the new staking contract must distribute the same reward token
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        migrate_msg("reward0002"),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RewardTokenMismatch {
+            expected: REWARD_TOKEN.to_string(),
+            actual: "foreign0000".to_string(),
+        }
+    );
+
+    // rewards are claimed and the whole position is unbonded
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        migrate_msg("reward0001"),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: REWARD_CONTRACT.to_string(),
+                funds: vec![],
+                msg: to_binary(&VkrExecuteMsg::Withdraw {}).unwrap(),
+            }),
+            SubMsg::reply_on_success(
+                WasmMsg::Execute {
+                    contract_addr: REWARD_CONTRACT.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&VkrExecuteMsg::Unbond {
+                        amount: Uint128::new(100),
+                    })
+                    .unwrap(),
+                },
+                MIGRATION_UNBOND_REPLY_ID
+            ),
+        ]
+    );
+    assert_eq!(
+        CONFIG
+            .load(deps.as_ref().storage)
+            .unwrap()
+            .reward_contract_addr,
+        Addr::unchecked("reward0001")
+    );
+
+    // the unbonded LP tokens are bonded into the new staking contract
+    deps.querier
+        .ledger_mut()
+        .unbond(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(100));
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        reply_ok(MIGRATION_UNBOND_REPLY_ID),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: LP_TOKEN.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "reward0001".to_string(),
+                    amount: Uint128::new(100),
+                    msg: to_binary(&VkrCw20HookMsg::Bond {}).unwrap(),
+                })
+                .unwrap(),
+            },
+            MIGRATION_BOND_REPLY_ID
+        )]
+    );
+
+    // the migration fails unless the new staking contract holds the whole position
+    let err = reply(deps.as_mut(), mock_env(), reply_ok(MIGRATION_BOND_REPLY_ID)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::StakingMigrationMismatch {
+            expected: Uint128::new(100),
+            actual: Uint128::zero(),
+        }
+    );
+
+    deps.querier
+        .ledger_mut()
+        .bond("reward0001", MOCK_CONTRACT_ADDR, Uint128::new(100));
+    let _res = reply(deps.as_mut(), mock_env(), reply_ok(MIGRATION_BOND_REPLY_ID)).unwrap();
+    assert!(STAKING_MIGRATION_CONTEXT
+        .may_load(deps.as_ref().storage)
+        .unwrap()
+        .is_none());
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Deposit {}).unwrap();
+    let deposit: Uint128 = from_binary(&res).unwrap();
+    assert_eq!(deposit, Uint128::new(100));
+}
+
+#[test]
+fn test_deprecate() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .ledger_mut()
+        .bond(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(100));
+
+    let msg = ProxyInstantiateMsgBuilder::new().owner("owner0000").build();
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    BONDED
+        .save(deps.as_mut().storage, &Uint128::new(100))
+        .unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Status {}).unwrap();
+    assert_eq!(
+        from_binary::<ProxyStatus>(&res).unwrap(),
+        ProxyStatus::Active
+    );
+
+    // only the owner can deprecate the proxy
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("generator0000", &[]),
+        ExecuteMsg::Deprecate {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the final rewards are claimed and the whole position is unbonded into the proxy
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::Deprecate {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: REWARD_CONTRACT.to_string(),
+                funds: vec![],
+                msg: to_binary(&VkrExecuteMsg::Withdraw {}).unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: REWARD_CONTRACT.to_string(),
+                funds: vec![],
+                msg: to_binary(&VkrExecuteMsg::Unbond {
+                    amount: Uint128::new(100),
+                })
+                .unwrap(),
+            }),
+        ]
+    );
+    assert_eq!(BONDED.load(deps.as_ref().storage).unwrap(), Uint128::zero());
+    assert_eq!(
+        RESERVED_LP.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(100)
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Status {}).unwrap();
+    assert_eq!(
+        from_binary::<ProxyStatus>(&res).unwrap(),
+        ProxyStatus::Deprecated
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::Deprecate {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ProxyDeprecated {});
+
+    // deposits and restaking are rejected
+    let deposit_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "generator0000".to_string(),
+        amount: Uint128::new(10),
+        msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
+    });
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(LP_TOKEN, &[]),
+        deposit_msg,
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ProxyDeprecated {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RestakeIdle {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ProxyDeprecated {});
+
+    // rewards are no longer claimed
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("generator0000", &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    // withdrawals are paid from the LP tokens held by the proxy
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("generator0000", &[]),
+        ExecuteMsg::Withdraw {
+            account: "addr0000".to_string(),
+            amount: Uint128::new(40),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: LP_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::new(40),
+            })
+            .unwrap(),
+        })]
+    );
+    assert_eq!(
+        RESERVED_LP.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(60)
+    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Deposit {}).unwrap();
+    assert_eq!(from_binary::<Uint128>(&res).unwrap(), Uint128::new(60));
+
+    // LP tokens owed to queued withdrawals are not deposited anymore
+    PENDING_WITHDRAWALS
+        .save(
+            deps.as_mut().storage,
+            &Addr::unchecked("addr0001"),
+            &vec![PendingWithdrawal {
+                amount: Uint128::new(30),
+                release_time: 0,
+            }],
+        )
+        .unwrap();
+    RESERVED_LP
+        .save(deps.as_mut().storage, &Uint128::new(90))
+        .unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Deposit {}).unwrap();
+    assert_eq!(from_binary::<Uint128>(&res).unwrap(), Uint128::new(60));
+}
+
+#[test]
+fn test_bond_failure_holds_lp() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = ProxyInstantiateMsgBuilder::new().owner("owner0000").build();
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let deposit_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "generator0000".to_string(),
+        amount: Uint128::new(100),
+        msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
+    });
+    let bond_msg = WasmMsg::Execute {
+        contract_addr: LP_TOKEN.to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: REWARD_CONTRACT.to_string(),
+            amount: Uint128::new(100),
+            msg: to_binary(&VkrCw20HookMsg::Bond {}).unwrap(),
+        })
+        .unwrap(),
+    };
+
+    // by default a failed bond reverts the deposit
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(LP_TOKEN, &[]),
+        deposit_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![SubMsg::new(bond_msg.clone())]);
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateSettings {
+            claim_rewards_on_withdraw: None,
+            unbonding_period: None,
+            hold_lp_on_bond_failure: Some(true),
+            harvest_interval: None,
+            harvest_bounty: None,
+            min_claim_amount: None,
+            reward_stream_period: None,
+        },
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(LP_TOKEN, &[]),
+        deposit_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_always(bond_msg, BOND_REPLY_ID)]
+    );
+    assert_eq!(
+        PENDING_BOND.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(100)
+    );
+
+    // a successful bond only clears the pending bond
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: BOND_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert!(res.events.is_empty());
+    assert!(PENDING_BOND
+        .may_load(deps.as_ref().storage)
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        BONDED.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(200)
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(LP_TOKEN, &[]),
+        deposit_msg,
+    )
+    .unwrap();
+
+    // the staking contract rejects the bond, the LP tokens stay in the proxy
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: BOND_REPLY_ID,
+            result: SubMsgResult::Err("staking paused".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.events.len(), 1);
+    assert_eq!(res.events[0].ty, "bond_failed");
+    assert_eq!(
+        res.events[0].attributes,
+        vec![attr("amount", "100"), attr("error", "staking paused")]
+    );
+    assert!(PENDING_BOND
+        .may_load(deps.as_ref().storage)
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        BONDED.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(200)
+    );
+    assert_eq!(
+        HELD_LP.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(100)
+    );
+
+    // the held LP tokens are part of the deposit and can be restaked
+    deps.querier
+        .ledger_mut()
+        .bond(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(200));
+    deps.querier.with_token_balances(&[(
+        &LP_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100))],
+    )]);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Deposit {}).unwrap();
+    assert_eq!(from_binary::<Uint128>(&res).unwrap(), Uint128::new(300));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::IdleBalance {}).unwrap();
+    let idle_balance: IdleBalanceResponse = from_binary(&res).unwrap();
+    assert_eq!(idle_balance.idle_amount, Uint128::new(100));
+    assert_eq!(idle_balance.held_amount, Uint128::new(100));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RestakeIdle {},
+    )
+    .unwrap();
+    assert_eq!(
+        BONDED.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(300)
+    );
+    assert_eq!(
+        HELD_LP.load(deps.as_ref().storage).unwrap(),
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn test_withdraw_pays_held_lp_first() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .ledger_mut()
+        .bond(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(100));
+    deps.querier
+        .ledger_mut()
+        .set_balance(LP_TOKEN, MOCK_CONTRACT_ADDR, Uint128::new(30));
+
+    let msg = ProxyInstantiateMsgBuilder::new().build();
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    BONDED
+        .save(deps.as_mut().storage, &Uint128::new(100))
+        .unwrap();
+    HELD_LP
+        .save(deps.as_mut().storage, &Uint128::new(30))
+        .unwrap();
+
+    let transfer_msg = |amount: u128| {
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: LP_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::new(amount),
+            })
+            .unwrap(),
+        })
+    };
+    let withdraw_msg = |amount: u128| ExecuteMsg::Withdraw {
+        account: "addr0000".to_string(),
+        amount: Uint128::new(amount),
+    };
+
+    // the held LP tokens cover the withdrawal, nothing is unbonded
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("generator0000", &[]),
+        withdraw_msg(20),
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![transfer_msg(20)]);
+    assert_eq!(
+        HELD_LP.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(10)
+    );
+    assert_eq!(
+        BONDED.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(100)
+    );
+    deps.querier
+        .ledger_mut()
+        .transfer(LP_TOKEN, MOCK_CONTRACT_ADDR, "addr0000", Uint128::new(20));
+
+    // the rest of the held LP tokens are paid out, only the remainder is unbonded
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("generator0000", &[]),
+        withdraw_msg(50),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            transfer_msg(10),
+            SubMsg::reply_on_success(
+                WasmMsg::Execute {
+                    contract_addr: REWARD_CONTRACT.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&VkrExecuteMsg::Unbond {
+                        amount: Uint128::new(40),
+                    })
+                    .unwrap(),
+                },
+                WITHDRAW_REPLY_ID
+            ),
+        ]
+    );
+    assert_eq!(
+        HELD_LP.load(deps.as_ref().storage).unwrap(),
+        Uint128::zero()
+    );
+    assert_eq!(
+        BONDED.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(60)
+    );
+
+    // the held LP tokens leave the proxy before the staking contract unbonds
+    deps.querier
+        .ledger_mut()
+        .transfer(LP_TOKEN, MOCK_CONTRACT_ADDR, "addr0000", Uint128::new(10));
+    deps.querier
+        .ledger_mut()
+        .unbond(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(40));
+    let res = reply(deps.as_mut(), mock_env(), reply_ok(WITHDRAW_REPLY_ID)).unwrap();
+    assert_eq!(res.messages, vec![transfer_msg(40)]);
+}
+
+#[test]
+fn test_harvest() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .ledger_mut()
+        .set_balance(REWARD_TOKEN, MOCK_CONTRACT_ADDR, Uint128::new(50));
+    deps.querier
+        .ledger_mut()
+        .accrue(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(1000));
+
+    let msg = ProxyInstantiateMsgBuilder::new().owner("owner0000").build();
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let update_settings_msg = |harvest_bounty: Decimal| ExecuteMsg::UpdateSettings {
+        claim_rewards_on_withdraw: None,
+        unbonding_period: None,
+        hold_lp_on_bond_failure: None,
+        harvest_interval: Some(100),
+        harvest_bounty: Some(harvest_bounty),
+        min_claim_amount: None,
+        reward_stream_period: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        update_settings_msg(Decimal::percent(11)),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::HarvestBountyTooHigh {
+            max: Decimal::percent(10),
+        }
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        update_settings_msg(Decimal::percent(2)),
+    )
+    .unwrap();
+
+    // the bounty can't be paid out without an interval between harvests
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateSettings {
+            claim_rewards_on_withdraw: None,
+            unbonding_period: None,
+            hold_lp_on_bond_failure: None,
+            harvest_interval: Some(0),
+            harvest_bounty: None,
+            min_claim_amount: None,
+            reward_stream_period: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::HarvestIntervalRequired {});
+
+    // anyone can harvest
+    let env = mock_env();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper0000", &[]),
+        ExecuteMsg::Harvest {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: REWARD_CONTRACT.to_string(),
+                funds: vec![],
+                msg: to_binary(&VkrExecuteMsg::Withdraw {}).unwrap(),
+            },
+            HARVEST_REPLY_ID
+        )]
+    );
+
+    // the keeper is paid its share of the claimed rewards only
+    deps.querier
+        .ledger_mut()
+        .claim(REWARD_CONTRACT, MOCK_CONTRACT_ADDR);
+    let res = reply(deps.as_mut(), env.clone(), reply_ok(HARVEST_REPLY_ID)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: REWARD_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "keeper0000".to_string(),
+                amount: Uint128::new(20),
+            })
+            .unwrap(),
+        })]
+    );
+    assert!(res.attributes.contains(&attr("amount", "1000")));
+
+    // the harvest interval is enforced
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::HarvestInfo {}).unwrap();
+    let harvest_info: HarvestInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        harvest_info,
+        HarvestInfoResponse {
+            last_harvest: env.block.time.seconds(),
+            next_harvest: env.block.time.seconds() + 100,
+        }
+    );
+
+    let mut env = env;
+    env.block.time = env.block.time.plus_seconds(99);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper0000", &[]),
+        ExecuteMsg::Harvest {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::HarvestTooSoon {
+            next_harvest: harvest_info.next_harvest,
+        }
+    );
+
+    env.block.time = env.block.time.plus_seconds(1);
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("keeper0000", &[]),
+        ExecuteMsg::Harvest {},
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_reward_streaming() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = ProxyInstantiateMsgBuilder::new().owner("owner0000").build();
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateSettings {
+            claim_rewards_on_withdraw: None,
+            unbonding_period: None,
+            hold_lp_on_bond_failure: None,
+            harvest_interval: None,
+            harvest_bounty: None,
+            min_claim_amount: None,
+            reward_stream_period: Some(100),
+        },
+    )
+    .unwrap();
+
+    let released_rewards = |deps: Deps, env: Env| -> Uint128 {
+        from_binary(&query(deps, env, QueryMsg::Reward {}).unwrap()).unwrap()
+    };
+    let mut env = mock_env();
+    let start_time = env.block.time.seconds();
+
+    // the claim replies to stream the claimed rewards
+    deps.querier
+        .ledger_mut()
+        .accrue(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(1000));
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(GENERATOR, &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: REWARD_CONTRACT.to_string(),
+                funds: vec![],
+                msg: to_binary(&VkrExecuteMsg::Withdraw {}).unwrap(),
+            },
+            STREAM_REPLY_ID
+        )]
+    );
+    deps.querier
+        .ledger_mut()
+        .claim(REWARD_CONTRACT, MOCK_CONTRACT_ADDR);
+    let res = reply(deps.as_mut(), env.clone(), reply_ok(STREAM_REPLY_ID)).unwrap();
+    assert!(res.attributes.contains(&attr("locked_amount", "1000")));
+
+    assert_eq!(
+        released_rewards(deps.as_ref(), env.clone()),
+        Uint128::zero()
+    );
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::PendingToken {}).unwrap();
+    assert_eq!(
+        from_binary::<Option<Uint128>>(&res).unwrap(),
+        Some(Uint128::new(1000))
+    );
+
+    // rewards are released linearly
+    env.block.time = env.block.time.plus_seconds(25);
+    assert_eq!(
+        released_rewards(deps.as_ref(), env.clone()),
+        Uint128::new(250)
+    );
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::StreamState {}).unwrap();
+    assert_eq!(
+        from_binary::<StreamStateResponse>(&res).unwrap(),
+        StreamStateResponse {
+            stream_period: 100,
+            start_time,
+            end_time: start_time + 100,
+            locked_amount: Uint128::new(750),
+            unscheduled_amount: Uint128::zero(),
+        }
+    );
+
+    // sent rewards leave the stream untouched
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(GENERATOR, &[]),
+        ExecuteMsg::SendRewards {
+            account: "user0000".to_string(),
+            amount: Uint128::new(250),
+        },
+    )
+    .unwrap();
+    deps.querier
+        .ledger_mut()
+        .set_balance(REWARD_TOKEN, MOCK_CONTRACT_ADDR, Uint128::new(750));
+    assert_eq!(
+        released_rewards(deps.as_ref(), env.clone()),
+        Uint128::zero()
+    );
+
+    // rewards claimed outside of reward updates wait for the next one
+    deps.querier
+        .ledger_mut()
+        .set_balance(REWARD_TOKEN, MOCK_CONTRACT_ADDR, Uint128::new(850));
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::StreamState {}).unwrap();
+    let stream_state: StreamStateResponse = from_binary(&res).unwrap();
+    assert_eq!(stream_state.unscheduled_amount, Uint128::new(100));
+    assert_eq!(
+        released_rewards(deps.as_ref(), env.clone()),
+        Uint128::zero()
+    );
+
+    // an overlapping claim releases the locked and the new rewards over a whole period
+    env.block.time = env.block.time.plus_seconds(25);
+    deps.querier
+        .ledger_mut()
+        .accrue(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(400));
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(GENERATOR, &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap();
+    deps.querier
+        .ledger_mut()
+        .claim(REWARD_CONTRACT, MOCK_CONTRACT_ADDR);
+    reply(deps.as_mut(), env.clone(), reply_ok(STREAM_REPLY_ID)).unwrap();
+
+    assert_eq!(
+        released_rewards(deps.as_ref(), env.clone()),
+        Uint128::new(250)
+    );
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::StreamState {}).unwrap();
+    assert_eq!(
+        from_binary::<StreamStateResponse>(&res).unwrap(),
+        StreamStateResponse {
+            stream_period: 100,
+            start_time: start_time + 50,
+            end_time: start_time + 150,
+            locked_amount: Uint128::new(1000),
+            unscheduled_amount: Uint128::zero(),
+        }
+    );
+
+    env.block.time = env.block.time.plus_seconds(50);
+    assert_eq!(
+        released_rewards(deps.as_ref(), env.clone()),
+        Uint128::new(750)
+    );
+    env.block.time = env.block.time.plus_seconds(50);
+    assert_eq!(released_rewards(deps.as_ref(), env), Uint128::new(1250));
+}
+
+#[test]
+fn test_reward_conversion() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .ledger_mut()
+        .set_balance("astro0000", MOCK_CONTRACT_ADDR, Uint128::zero());
+    let msg = ProxyInstantiateMsgBuilder::new().owner("owner0000").build();
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let token = |contract_addr: &str| AssetInfo::Token {
+        contract_addr: Addr::unchecked(contract_addr),
+    };
+    let conversion_msg =
+        |route: Vec<AssetInfo>, max_spread: Decimal| ExecuteMsg::UpdateRewardConversion {
+            conversion: Some(RewardConversionInfo {
+                router: "router0000".to_string(),
+                route,
+                max_spread,
+                belief_price: None,
+            }),
+        };
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        conversion_msg(vec![token("astro0000")], Decimal::percent(2)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    for (msg, expected) in [
+        (
+            conversion_msg(vec![], Decimal::percent(2)),
+            ContractError::EmptyConversionRoute {},
+        ),
+        (
+            conversion_msg(
+                vec![AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                }],
+                Decimal::percent(2),
+            ),
+            ContractError::NativeConversionTarget {},
+        ),
+        (
+            conversion_msg(vec![token("astro0000")], Decimal::percent(6)),
+            ContractError::ConversionSpreadTooHigh {
+                max: Decimal::percent(5),
+            },
+        ),
+        (
+            ExecuteMsg::UpdateRewardConversion {
+                conversion: Some(RewardConversionInfo {
+                    router: "router0000".to_string(),
+                    route: vec![token("astro0000")],
+                    max_spread: Decimal::percent(2),
+                    belief_price: Some(Decimal::zero()),
+                }),
+            },
+            ContractError::ZeroBeliefPrice {},
+        ),
+    ] {
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap_err();
+        assert_eq!(err, expected);
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        conversion_msg(
+            vec![token("usdc0000"), token("astro0000")],
+            Decimal::percent(2),
+        ),
+    )
+    .unwrap();
+    let operations = vec![
+        SwapOperation::AstroSwap {
+            offer_asset_info: token(REWARD_TOKEN),
+            ask_asset_info: token("usdc0000"),
+        },
+        SwapOperation::AstroSwap {
+            offer_asset_info: token("usdc0000"),
+            ask_asset_info: token("astro0000"),
+        },
+    ];
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::RewardConversion {}).unwrap();
+    assert_eq!(
+        from_binary::<Option<RewardConversionResponse>>(&res).unwrap(),
+        Some(RewardConversionResponse {
+            router: Addr::unchecked("router0000"),
+            operations: operations.clone(),
+            target_token: Addr::unchecked("astro0000"),
+            max_spread: Decimal::percent(2),
+            belief_price: None,
+        })
+    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::RewardInfo {}).unwrap();
+    assert_eq!(
+        from_binary::<Addr>(&res).unwrap(),
+        Addr::unchecked("astro0000")
+    );
+
+    // the claimed rewards are swapped once the claim replies
+    deps.querier
+        .ledger_mut()
+        .accrue(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(1000));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(GENERATOR, &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: REWARD_CONTRACT.to_string(),
+                funds: vec![],
+                msg: to_binary(&VkrExecuteMsg::Withdraw {}).unwrap(),
+            },
+            CONVERT_REPLY_ID
+        )]
+    );
+    deps.querier
+        .ledger_mut()
+        .claim(REWARD_CONTRACT, MOCK_CONTRACT_ADDR);
+    let res = reply(deps.as_mut(), mock_env(), reply_ok(CONVERT_REPLY_ID)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_always(
+            WasmMsg::Execute {
+                contract_addr: REWARD_TOKEN.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "router0000".to_string(),
+                    amount: Uint128::new(1000),
+                    msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                        operations,
+                        minimum_receive: None,
+                        to: None,
+                        max_spread: Some(Decimal::percent(2)),
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+            },
+            SWAP_REPLY_ID
+        )]
+    );
+
+    // a failed swap keeps the reward tokens for the next reward update
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: SWAP_REPLY_ID,
+            result: SubMsgResult::Err("Operation exceeds max spread limit".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "convert_rewards"),
+            attr("conversion_failed", "Operation exceeds max spread limit"),
+        ]
+    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Reward {}).unwrap();
+    assert_eq!(from_binary::<Uint128>(&res).unwrap(), Uint128::zero());
+
+    // the converted rewards are reported and sent
+    deps.querier.ledger_mut().transfer(
+        REWARD_TOKEN,
+        MOCK_CONTRACT_ADDR,
+        "router0000",
+        Uint128::new(1000),
+    );
+    deps.querier
+        .ledger_mut()
+        .mint("astro0000", MOCK_CONTRACT_ADDR, Uint128::new(950));
+    reply(deps.as_mut(), mock_env(), reply_ok(SWAP_REPLY_ID)).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Reward {}).unwrap();
+    assert_eq!(from_binary::<Uint128>(&res).unwrap(), Uint128::new(950));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(GENERATOR, &[]),
+        ExecuteMsg::SendRewards {
+            account: "user0000".to_string(),
+            amount: Uint128::new(950),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "astro0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user0000".to_string(),
+                amount: Uint128::new(950),
+            })
+            .unwrap(),
+        })]
+    );
+
+    // the distributed token can not change once LP tokens are deposited
+    deps.querier
+        .ledger_mut()
+        .bond(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(100));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateRewardConversion { conversion: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::DistributedTokenLocked {});
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateRewardConversion {
+            conversion: Some(RewardConversionInfo {
+                router: "router0000".to_string(),
+                route: vec![token("astro0000")],
+                max_spread: Decimal::percent(5),
+                belief_price: Some(Decimal::percent(95)),
+            }),
+        },
+    )
+    .unwrap();
+
+    // the belief price sets the minimum received by the swap
+    deps.querier
+        .ledger_mut()
+        .accrue(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(1000));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(GENERATOR, &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap();
+    deps.querier
+        .ledger_mut()
+        .claim(REWARD_CONTRACT, MOCK_CONTRACT_ADDR);
+    let res = reply(deps.as_mut(), mock_env(), reply_ok(CONVERT_REPLY_ID)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_always(
+            WasmMsg::Execute {
+                contract_addr: REWARD_TOKEN.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "router0000".to_string(),
+                    amount: Uint128::new(1000),
+                    msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                        operations: vec![SwapOperation::AstroSwap {
+                            offer_asset_info: token(REWARD_TOKEN),
+                            ask_asset_info: token("astro0000"),
+                        }],
+                        minimum_receive: Some(Uint128::new(902)),
+                        to: None,
+                        max_spread: Some(Decimal::percent(5)),
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+            },
+            SWAP_REPLY_ID
+        )]
+    );
+}
+
+#[test]
+fn test_reward_splits() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = ProxyInstantiateMsgBuilder::new().owner("owner0000").build();
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let split = |recipient: &str, weight: u64| RewardSplitInfo {
+        recipient: recipient.to_string(),
+        weight: Decimal::percent(weight),
+    };
+    let update_splits = |deps: DepsMut, sender: &str, splits: Vec<RewardSplitInfo>| {
+        execute(
+            deps,
+            mock_env(),
+            mock_info(sender, &[]),
+            ExecuteMsg::UpdateRewardSplits { splits },
+        )
+    };
+
+    let res = update_splits(
+        deps.as_mut(),
+        "addr0000",
+        vec![split(GENERATOR, 80), split("treasury0000", 20)],
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+    let res = update_splits(
+        deps.as_mut(),
+        "owner0000",
+        vec![split(GENERATOR, 80), split("treasury0000", 10)],
+    );
+    assert_eq!(res.unwrap_err(), ContractError::InvalidSplitWeights {});
+    let res = update_splits(
+        deps.as_mut(),
+        "owner0000",
+        vec![split(GENERATOR, 100), split("treasury0000", 0)],
+    );
+    assert_eq!(res.unwrap_err(), ContractError::InvalidSplitWeights {});
+    let res = update_splits(
+        deps.as_mut(),
+        "owner0000",
+        vec![split("treasury0000", 50), split("treasury0000", 50)],
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::DuplicateSplitRecipient {
+            recipient: "treasury0000".to_string()
+        }
+    );
+    update_splits(
+        deps.as_mut(),
+        "owner0000",
+        vec![split(GENERATOR, 80), split("treasury0000", 20)],
+    )
+    .unwrap();
+
+    // the generator is only reported its share of the pending rewards
+    deps.querier
+        .ledger_mut()
+        .accrue(REWARD_CONTRACT, MOCK_CONTRACT_ADDR, Uint128::new(1000));
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingToken {}).unwrap();
+    assert_eq!(
+        from_binary::<Option<Uint128>>(&res).unwrap(),
+        Some(Uint128::new(800))
+    );
+
+    // claimed rewards accrue to the beneficiaries
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(GENERATOR, &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap();
+    deps.querier
+        .ledger_mut()
+        .claim(REWARD_CONTRACT, MOCK_CONTRACT_ADDR);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Reward {}).unwrap();
+    assert_eq!(from_binary::<Uint128>(&res).unwrap(), Uint128::new(800));
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::RewardSplits {}).unwrap();
+    assert_eq!(
+        from_binary::<Vec<RewardSplitResponse>>(&res).unwrap(),
+        vec![
+            RewardSplitResponse {
+                recipient: Addr::unchecked(GENERATOR),
+                weight: Decimal::percent(80),
+                accrued_amount: Uint128::zero(),
+            },
+            RewardSplitResponse {
+                recipient: Addr::unchecked("treasury0000"),
+                weight: Decimal::percent(20),
+                accrued_amount: Uint128::new(200),
+            },
+        ]
+    );
+
+    // sending the generator share keeps the accrued rewards
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(GENERATOR, &[]),
+        ExecuteMsg::SendRewards {
+            account: "user0000".to_string(),
+            amount: Uint128::new(800),
+        },
+    )
+    .unwrap();
+    deps.querier
+        .ledger_mut()
+        .set_balance(REWARD_TOKEN, MOCK_CONTRACT_ADDR, Uint128::new(200));
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Reward {}).unwrap();
+    assert_eq!(from_binary::<Uint128>(&res).unwrap(), Uint128::zero());
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AccruedSplit {
+            recipient: "treasury0000".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(from_binary::<Uint128>(&res).unwrap(), Uint128::new(200));
+
+    // removed beneficiaries keep their accrued rewards
+    update_splits(deps.as_mut(), "owner0000", vec![]).unwrap();
+    deps.querier
+        .ledger_mut()
+        .set_balance(REWARD_TOKEN, MOCK_CONTRACT_ADDR, Uint128::new(300));
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Reward {}).unwrap();
+    assert_eq!(from_binary::<Uint128>(&res).unwrap(), Uint128::new(100));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("treasury0000", &[]),
+        ExecuteMsg::ClaimSplit {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: REWARD_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "treasury0000".to_string(),
+                amount: Uint128::new(200),
+            })
+            .unwrap(),
+        })]
+    );
+    deps.querier
+        .ledger_mut()
+        .set_balance(REWARD_TOKEN, MOCK_CONTRACT_ADDR, Uint128::new(100));
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Reward {}).unwrap();
+    assert_eq!(from_binary::<Uint128>(&res).unwrap(), Uint128::new(100));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("treasury0000", &[]),
+        ExecuteMsg::ClaimSplit {},
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::NoAccruedRewards {
+            recipient: "treasury0000".to_string()
+        }
+    );
+}
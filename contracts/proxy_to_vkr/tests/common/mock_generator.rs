@@ -0,0 +1,129 @@
+//! Minimal stand-in for the Astroport generator.
+//! Forwards LP deposits to the proxy and tracks the amount deposited by every user.
+
+use ap_valkyrie::ProxyContract;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Uint128,
+};
+use cw20::Cw20ReceiveMsg;
+use cw_storage_plus::{Item, Map};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub lp_token: String,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Sets the proxy once it is instantiated with this contract as the generator
+    SetProxy {
+        proxy: String,
+    },
+    Receive(Cw20ReceiveMsg),
+    /// Withdraws the sender LP tokens through the proxy
+    Withdraw {
+        amount: Uint128,
+    },
+    /// Forwards UpdateRewards to the proxy
+    UpdateRewards {},
+    /// Forwards SendRewards to the proxy
+    SendRewards {
+        account: String,
+        amount: Uint128,
+    },
+}
+
+#[cw_serde]
+pub enum Cw20HookMsg {
+    Deposit {},
+}
+
+#[cw_serde]
+pub enum QueryMsg {
+    Deposit { user: String },
+}
+
+const OWNER: Item<Addr> = Item::new("owner");
+const LP_TOKEN: Item<Addr> = Item::new("lp_token");
+const PROXY: Item<ProxyContract> = Item::new("proxy");
+const DEPOSITS: Map<&Addr, Uint128> = Map::new("deposits");
+
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    OWNER.save(deps.storage, &info.sender)?;
+    LP_TOKEN.save(deps.storage, &deps.api.addr_validate(&msg.lp_token)?)?;
+    Ok(Response::new())
+}
+
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::SetProxy { proxy } => {
+            assert_owner(deps.as_ref(), &info)?;
+            PROXY.save(
+                deps.storage,
+                &ProxyContract(deps.api.addr_validate(&proxy)?),
+            )?;
+            Ok(Response::new())
+        }
+        ExecuteMsg::Receive(cw20_msg) => {
+            let lp_token = LP_TOKEN.load(deps.storage)?;
+            if info.sender != lp_token {
+                return Err(StdError::generic_err("unauthorized"));
+            }
+            let Cw20HookMsg::Deposit {} = from_binary(&cw20_msg.msg)?;
+
+            let user = deps.api.addr_validate(&cw20_msg.sender)?;
+            DEPOSITS.update(deps.storage, &user, |deposit| -> StdResult<_> {
+                Ok(deposit.unwrap_or_default() + cw20_msg.amount)
+            })?;
+
+            let proxy = PROXY.load(deps.storage)?;
+            Ok(Response::new().add_message(proxy.deposit_msg(lp_token, cw20_msg.amount)?))
+        }
+        ExecuteMsg::Withdraw { amount } => {
+            DEPOSITS.update(deps.storage, &info.sender, |deposit| -> StdResult<_> {
+                Ok(deposit.unwrap_or_default().checked_sub(amount)?)
+            })?;
+
+            let proxy = PROXY.load(deps.storage)?;
+            Ok(Response::new().add_message(proxy.withdraw_msg(info.sender, amount)?))
+        }
+        ExecuteMsg::UpdateRewards {} => {
+            assert_owner(deps.as_ref(), &info)?;
+            let proxy = PROXY.load(deps.storage)?;
+            Ok(Response::new().add_message(proxy.update_rewards_msg()?))
+        }
+        ExecuteMsg::SendRewards { account, amount } => {
+            assert_owner(deps.as_ref(), &info)?;
+            let proxy = PROXY.load(deps.storage)?;
+            Ok(Response::new().add_message(proxy.send_rewards_msg(account, amount)?))
+        }
+    }
+}
+
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Deposit { user } => {
+            let user = deps.api.addr_validate(&user)?;
+            to_binary(&DEPOSITS.may_load(deps.storage, &user)?.unwrap_or_default())
+        }
+    }
+}
+
+fn assert_owner(deps: Deps, info: &MessageInfo) -> StdResult<()> {
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+    Ok(())
+}
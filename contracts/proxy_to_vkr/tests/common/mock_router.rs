@@ -0,0 +1,123 @@
+//! Local stand-in for the Astroport router.
+//! Swaps received tokens at a fixed rate for the ask token it holds, failing when the requested max
+//! spread is below its spread or the return is below the requested minimum.
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::Item;
+
+pub use ap_valkyrie::router::{
+    AssetInfo, Cw20HookMsg, QueryMsg, SimulateSwapOperationsResponse, SwapOperation,
+};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub ask_token: String,
+    /// Amount of ask tokens paid per offered token
+    pub rate: Decimal,
+    /// Spread of every swap, swaps with a lower max spread fail
+    pub spread: Decimal,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+}
+
+#[cw_serde]
+struct Config {
+    ask_token: Addr,
+    rate: Decimal,
+    spread: Decimal,
+}
+
+const CONFIG: Item<Config> = Item::new("config");
+
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            ask_token: deps.api.addr_validate(&msg.ask_token)?,
+            rate: msg.rate,
+            spread: msg.spread,
+        },
+    )?;
+    Ok(Response::new())
+}
+
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: ExecuteMsg,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    let ExecuteMsg::Receive(cw20_msg) = msg;
+    let Cw20HookMsg::ExecuteSwapOperations {
+        operations,
+        minimum_receive,
+        to,
+        max_spread,
+    } = from_binary(&cw20_msg.msg)?;
+
+    assert_ask_token(&config, &operations)?;
+    if max_spread.unwrap_or_default() < config.spread {
+        return Err(StdError::generic_err("Operation exceeds max spread limit"));
+    }
+
+    let amount = cw20_msg.amount * config.rate;
+    if let Some(minimum_receive) = minimum_receive {
+        if amount < minimum_receive {
+            return Err(StdError::generic_err(format!(
+                "Assertion failed; minimum receive amount: {}, swap amount: {}",
+                minimum_receive, amount
+            )));
+        }
+    }
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: config.ask_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: to.unwrap_or(cw20_msg.sender),
+                amount,
+            })?,
+            funds: vec![],
+        })
+        .add_attribute("action", "swap")
+        .add_attribute("return_amount", amount))
+}
+
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    match msg {
+        QueryMsg::SimulateSwapOperations {
+            offer_amount,
+            operations,
+        } => {
+            assert_ask_token(&config, &operations)?;
+            to_binary(&SimulateSwapOperationsResponse {
+                amount: offer_amount * config.rate,
+            })
+        }
+    }
+}
+
+/// The last swap must ask for the token held by the router
+fn assert_ask_token(config: &Config, operations: &[SwapOperation]) -> StdResult<()> {
+    match operations.last() {
+        Some(SwapOperation::AstroSwap {
+            ask_asset_info: AssetInfo::Token { contract_addr },
+            ..
+        }) if *contract_addr == config.ask_token => Ok(()),
+        _ => Err(StdError::generic_err("Unknown ask asset")),
+    }
+}
@@ -0,0 +1,202 @@
+//! Local stand-in for the Valkyrie LP staking contract.
+//! Distributes a fixed amount of reward tokens per block between all bonded LP tokens.
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, Storage, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use cw_storage_plus::{Item, Map};
+
+pub use ap_valkyrie::lp_staking::{Cw20HookMsg, ExecuteMsg, QueryMsg, StakerInfoResponse};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub token: String,
+    pub pair: String,
+    pub lp_token: String,
+    pub reward_per_block: Uint128,
+}
+
+/// Full upstream config, the proxy only reads a subset of it
+#[cw_serde]
+pub struct ConfigResponse {
+    pub token: String,
+    pub pair: String,
+    pub lp_token: String,
+    pub whitelisted_contracts: Vec<String>,
+    pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+}
+
+#[cw_serde]
+struct Config {
+    token: Addr,
+    pair: Addr,
+    lp_token: Addr,
+    reward_per_block: Uint128,
+}
+
+#[cw_serde]
+struct State {
+    global_index: Decimal,
+    total_bond_amount: Uint128,
+    last_distributed: u64,
+}
+
+#[cw_serde]
+#[derive(Default)]
+struct StakerInfo {
+    reward_index: Decimal,
+    bond_amount: Uint128,
+    pending_reward: Uint128,
+}
+
+const CONFIG: Item<Config> = Item::new("config");
+const STATE: Item<State> = Item::new("state");
+const STAKERS: Map<&Addr, StakerInfo> = Map::new("stakers");
+
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            token: deps.api.addr_validate(&msg.token)?,
+            pair: deps.api.addr_validate(&msg.pair)?,
+            lp_token: deps.api.addr_validate(&msg.lp_token)?,
+            reward_per_block: msg.reward_per_block,
+        },
+    )?;
+    STATE.save(
+        deps.storage,
+        &State {
+            global_index: Decimal::zero(),
+            total_bond_amount: Uint128::zero(),
+            last_distributed: env.block.height,
+        },
+    )?;
+
+    Ok(Response::new())
+}
+
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = distribute(deps.storage, &env, &config)?;
+
+    match msg {
+        ExecuteMsg::Receive(cw20_msg) => {
+            if info.sender != config.lp_token {
+                return Err(StdError::generic_err("unauthorized"));
+            }
+            let Cw20HookMsg::Bond {} = from_binary(&cw20_msg.msg)?;
+
+            let staker = deps.api.addr_validate(&cw20_msg.sender)?;
+            let mut staker_info = staker_info(deps.storage, &state, &staker)?;
+            staker_info.bond_amount += cw20_msg.amount;
+            state.total_bond_amount += cw20_msg.amount;
+
+            STAKERS.save(deps.storage, &staker, &staker_info)?;
+            STATE.save(deps.storage, &state)?;
+
+            Ok(Response::new().add_attribute("action", "bond"))
+        }
+        ExecuteMsg::Unbond { amount } => {
+            let mut staker_info = staker_info(deps.storage, &state, &info.sender)?;
+            staker_info.bond_amount = staker_info
+                .bond_amount
+                .checked_sub(amount)
+                .map_err(|_| StdError::generic_err("Cannot unbond more than bond amount"))?;
+            state.total_bond_amount -= amount;
+
+            STAKERS.save(deps.storage, &info.sender, &staker_info)?;
+            STATE.save(deps.storage, &state)?;
+
+            Ok(Response::new()
+                .add_message(WasmMsg::Execute {
+                    contract_addr: config.lp_token.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: info.sender.to_string(),
+                        amount,
+                    })?,
+                    funds: vec![],
+                })
+                .add_attribute("action", "unbond"))
+        }
+        ExecuteMsg::Withdraw {} => {
+            let mut staker_info = staker_info(deps.storage, &state, &info.sender)?;
+            let amount = staker_info.pending_reward;
+            staker_info.pending_reward = Uint128::zero();
+
+            STAKERS.save(deps.storage, &info.sender, &staker_info)?;
+            STATE.save(deps.storage, &state)?;
+
+            let mut response = Response::new().add_attribute("action", "withdraw");
+            if !amount.is_zero() {
+                response = response.add_message(WasmMsg::Execute {
+                    contract_addr: config.token.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: info.sender.to_string(),
+                        amount,
+                    })?,
+                    funds: vec![],
+                });
+            }
+            Ok(response)
+        }
+    }
+}
+
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    match msg {
+        QueryMsg::Config {} => to_binary(&ConfigResponse {
+            token: config.token.to_string(),
+            pair: config.pair.to_string(),
+            lp_token: config.lp_token.to_string(),
+            whitelisted_contracts: vec![],
+            distribution_schedule: vec![],
+        }),
+        QueryMsg::StakerInfo { staker } => {
+            let staker = deps.api.addr_validate(&staker)?;
+            let state = compute_state(STATE.load(deps.storage)?, &env, &config);
+            let staker_info = staker_info(deps.storage, &state, &staker)?;
+
+            to_binary(&StakerInfoResponse {
+                staker: staker.to_string(),
+                reward_index: staker_info.reward_index,
+                bond_amount: staker_info.bond_amount,
+                pending_reward: staker_info.pending_reward,
+            })
+        }
+    }
+}
+
+/// Accrues the rewards of the blocks passed since the last distribution
+fn compute_state(mut state: State, env: &Env, config: &Config) -> State {
+    let blocks = env.block.height - state.last_distributed;
+    if !state.total_bond_amount.is_zero() && blocks > 0 {
+        let distributed = config.reward_per_block * Uint128::from(blocks);
+        state.global_index += Decimal::from_ratio(distributed, state.total_bond_amount);
+    }
+    state.last_distributed = env.block.height;
+    state
+}
+
+fn distribute(storage: &mut dyn Storage, env: &Env, config: &Config) -> StdResult<State> {
+    let state = compute_state(STATE.load(storage)?, env, config);
+    STATE.save(storage, &state)?;
+    Ok(state)
+}
+
+/// Returns the staker position with the rewards accrued up to the given state
+fn staker_info(storage: &dyn Storage, state: &State, staker: &Addr) -> StdResult<StakerInfo> {
+    let mut staker_info = STAKERS.may_load(storage, staker)?.unwrap_or_default();
+    staker_info.pending_reward +=
+        staker_info.bond_amount * (state.global_index - staker_info.reward_index);
+    staker_info.reward_index = state.global_index;
+    Ok(staker_info)
+}
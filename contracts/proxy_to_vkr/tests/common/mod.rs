@@ -0,0 +1,385 @@
+#![allow(dead_code)]
+
+pub mod mock_generator;
+pub mod mock_router;
+pub mod mock_valkyrie;
+
+use anyhow::Result as AnyResult;
+use ap_valkyrie::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use cosmwasm_schema::serde::de::DeserializeOwned;
+use cosmwasm_std::{to_binary, Addr, Decimal, StdResult, Uint128};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
+use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
+
+use mock_valkyrie::StakerInfoResponse;
+
+pub const OWNER: &str = "owner";
+pub const PAIR: &str = "pair";
+pub const USERS: [&str; 3] = ["user1", "user2", "user3"];
+/// LP tokens every user starts with
+pub const USER_LP_BALANCE: u128 = 1_000_000;
+/// Reward tokens held by the staking contract for distribution
+pub const REWARD_SUPPLY: u128 = 1_000_000_000_000;
+/// Ask tokens held by a router for swaps
+pub const ROUTER_SUPPLY: u128 = 1_000_000_000_000;
+
+/// Multi-test environment with a cw20 LP token, a cw20 reward token, a Valkyrie LP staking
+/// stand-in, a generator stand-in and the proxy wired together
+pub struct Suite {
+    pub app: App,
+    pub owner: Addr,
+    pub lp_token: Addr,
+    pub reward_token: Addr,
+    pub staking: Addr,
+    pub generator: Addr,
+    pub proxy: Addr,
+    pub proxy_code_id: u64,
+    pub staking_code_id: u64,
+    pub cw20_code_id: u64,
+}
+
+impl Suite {
+    pub fn new(reward_per_block: u128) -> Self {
+        let mut app = App::default();
+        let owner = Addr::unchecked(OWNER);
+
+        let cw20_code_id = app.store_code(Box::new(ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        )));
+        let staking_code_id = app.store_code(Box::new(ContractWrapper::new(
+            mock_valkyrie::execute,
+            mock_valkyrie::instantiate,
+            mock_valkyrie::query,
+        )));
+        let generator_code_id = app.store_code(Box::new(ContractWrapper::new(
+            mock_generator::execute,
+            mock_generator::instantiate,
+            mock_generator::query,
+        )));
+        let proxy_code_id = store_proxy_code(&mut app);
+
+        let lp_token = instantiate_token(
+            &mut app,
+            cw20_code_id,
+            "ASTRO-VKR-LP",
+            USERS
+                .iter()
+                .map(|user| Cw20Coin {
+                    address: user.to_string(),
+                    amount: Uint128::new(USER_LP_BALANCE),
+                })
+                .collect(),
+        );
+        let reward_token = instantiate_token(&mut app, cw20_code_id, "VKR", vec![]);
+
+        let staking = instantiate_staking(
+            &mut app,
+            staking_code_id,
+            &lp_token,
+            &reward_token,
+            reward_per_block,
+        );
+
+        let generator = app
+            .instantiate_contract(
+                generator_code_id,
+                owner.clone(),
+                &mock_generator::InstantiateMsg {
+                    lp_token: lp_token.to_string(),
+                },
+                &[],
+                "Generator",
+                None,
+            )
+            .unwrap();
+
+        let proxy = app
+            .instantiate_contract(
+                proxy_code_id,
+                owner.clone(),
+                &InstantiateMsg {
+                    generator_contract_addr: generator.to_string(),
+                    pair_addr: PAIR.to_string(),
+                    lp_token_addr: lp_token.to_string(),
+                    reward_contract_addr: staking.to_string(),
+                    reward_token_addr: reward_token.to_string(),
+                    owner: None,
+                },
+                &[],
+                "Generator proxy to VKR",
+                Some(owner.to_string()),
+            )
+            .unwrap();
+        app.execute_contract(
+            owner.clone(),
+            generator.clone(),
+            &mock_generator::ExecuteMsg::SetProxy {
+                proxy: proxy.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        Suite {
+            app,
+            owner,
+            lp_token,
+            reward_token,
+            staking,
+            generator,
+            proxy,
+            proxy_code_id,
+            staking_code_id,
+            cw20_code_id,
+        }
+    }
+
+    /// Advances the chain by the given number of blocks
+    pub fn next_blocks(&mut self, blocks: u64) {
+        self.app.update_block(|block| {
+            block.height += blocks;
+            block.time = block.time.plus_seconds(5 * blocks);
+        });
+    }
+
+    /// Deposits LP tokens of the user through the generator
+    pub fn deposit(&mut self, user: &str, amount: u128) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(user),
+            self.lp_token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.generator.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(&mock_generator::Cw20HookMsg::Deposit {})?,
+            },
+            &[],
+        )
+    }
+
+    /// Withdraws LP tokens of the user through the generator
+    pub fn withdraw(&mut self, user: &str, amount: u128) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(user),
+            self.generator.clone(),
+            &mock_generator::ExecuteMsg::Withdraw {
+                amount: Uint128::new(amount),
+            },
+            &[],
+        )
+    }
+
+    /// Claims rewards from the staking contract through the generator
+    pub fn update_rewards(&mut self) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            self.owner.clone(),
+            self.generator.clone(),
+            &mock_generator::ExecuteMsg::UpdateRewards {},
+            &[],
+        )
+    }
+
+    /// Sends rewards held by the proxy to the account through the generator
+    pub fn send_rewards(&mut self, account: &str, amount: u128) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            self.owner.clone(),
+            self.generator.clone(),
+            &mock_generator::ExecuteMsg::SendRewards {
+                account: account.to_string(),
+                amount: Uint128::new(amount),
+            },
+            &[],
+        )
+    }
+
+    /// Executes a proxy message on behalf of the sender
+    pub fn execute_proxy(&mut self, sender: &str, msg: &ExecuteMsg) -> AnyResult<AppResponse> {
+        self.app
+            .execute_contract(Addr::unchecked(sender), self.proxy.clone(), msg, &[])
+    }
+
+    /// Sends LP tokens to the proxy outside of the deposit flow
+    pub fn donate_lp(&mut self, user: &str, amount: u128) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(user),
+            self.lp_token.clone(),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: self.proxy.to_string(),
+                amount: Uint128::new(amount),
+            },
+            &[],
+        )
+    }
+
+    pub fn query_proxy<T: DeserializeOwned>(&self, msg: &QueryMsg) -> StdResult<T> {
+        self.app.wrap().query_wasm_smart(&self.proxy, msg)
+    }
+
+    pub fn staker_info(&self) -> StakerInfoResponse {
+        self.staker_info_at(&self.staking)
+    }
+
+    /// Returns the proxy position in the given staking contract
+    pub fn staker_info_at(&self, staking: &Addr) -> StakerInfoResponse {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                staking,
+                &mock_valkyrie::QueryMsg::StakerInfo {
+                    staker: self.proxy.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    /// Instantiates another staking contract for the same LP and reward tokens
+    pub fn instantiate_staking(&mut self, reward_per_block: u128) -> Addr {
+        instantiate_staking(
+            &mut self.app,
+            self.staking_code_id,
+            &self.lp_token,
+            &self.reward_token,
+            reward_per_block,
+        )
+    }
+
+    /// Instantiates a router swapping to a new token at the given rate, failing below the spread.
+    /// Returns the router and the token it swaps to
+    pub fn instantiate_router(&mut self, rate: Decimal, spread: Decimal) -> (Addr, Addr) {
+        let router_code_id = self.app.store_code(Box::new(ContractWrapper::new(
+            mock_router::execute,
+            mock_router::instantiate,
+            mock_router::query,
+        )));
+        let ask_token = instantiate_token(&mut self.app, self.cw20_code_id, "ASTRO", vec![]);
+        let router = self
+            .app
+            .instantiate_contract(
+                router_code_id,
+                self.owner.clone(),
+                &mock_router::InstantiateMsg {
+                    ask_token: ask_token.to_string(),
+                    rate,
+                    spread,
+                },
+                &[],
+                "Astroport router",
+                None,
+            )
+            .unwrap();
+        self.app
+            .execute_contract(
+                self.owner.clone(),
+                ask_token.clone(),
+                &Cw20ExecuteMsg::Mint {
+                    recipient: router.to_string(),
+                    amount: Uint128::new(ROUTER_SUPPLY),
+                },
+                &[],
+            )
+            .unwrap();
+        (router, ask_token)
+    }
+
+    pub fn token_balance(&self, token: &Addr, address: impl Into<String>) -> u128 {
+        token_balance(&self.app, token, address)
+    }
+
+    pub fn lp_balance(&self, address: impl Into<String>) -> u128 {
+        token_balance(&self.app, &self.lp_token, address)
+    }
+
+    pub fn reward_balance(&self, address: impl Into<String>) -> u128 {
+        token_balance(&self.app, &self.reward_token, address)
+    }
+}
+
+/// Instantiates a staking contract holding the whole reward supply
+fn instantiate_staking(
+    app: &mut App,
+    code_id: u64,
+    lp_token: &Addr,
+    reward_token: &Addr,
+    reward_per_block: u128,
+) -> Addr {
+    let owner = Addr::unchecked(OWNER);
+    let staking = app
+        .instantiate_contract(
+            code_id,
+            owner.clone(),
+            &mock_valkyrie::InstantiateMsg {
+                token: reward_token.to_string(),
+                pair: PAIR.to_string(),
+                lp_token: lp_token.to_string(),
+                reward_per_block: Uint128::new(reward_per_block),
+            },
+            &[],
+            "Valkyrie LP staking",
+            None,
+        )
+        .unwrap();
+    app.execute_contract(
+        owner,
+        reward_token.clone(),
+        &Cw20ExecuteMsg::Mint {
+            recipient: staking.to_string(),
+            amount: Uint128::new(REWARD_SUPPLY),
+        },
+        &[],
+    )
+    .unwrap();
+    staking
+}
+
+pub fn store_proxy_code(app: &mut App) -> u64 {
+    app.store_code(Box::new(
+        ContractWrapper::new(
+            generator_proxy_to_vkr::contract::execute,
+            generator_proxy_to_vkr::contract::instantiate,
+            generator_proxy_to_vkr::contract::query,
+        )
+        .with_reply(generator_proxy_to_vkr::contract::reply)
+        .with_migrate(generator_proxy_to_vkr::contract::migrate),
+    ))
+}
+
+fn instantiate_token(
+    app: &mut App,
+    code_id: u64,
+    symbol: &str,
+    initial_balances: Vec<Cw20Coin>,
+) -> Addr {
+    app.instantiate_contract(
+        code_id,
+        Addr::unchecked(OWNER),
+        &cw20_base::msg::InstantiateMsg {
+            name: format!("{} token", symbol),
+            symbol: symbol.to_string(),
+            decimals: 6,
+            initial_balances,
+            mint: Some(MinterResponse {
+                minter: OWNER.to_string(),
+                cap: None,
+            }),
+            marketing: None,
+        },
+        &[],
+        symbol,
+        None,
+    )
+    .unwrap()
+}
+
+fn token_balance(app: &App, token: &Addr, address: impl Into<String>) -> u128 {
+    let res: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token,
+            &Cw20QueryMsg::Balance {
+                address: address.into(),
+            },
+        )
+        .unwrap();
+    res.balance.u128()
+}
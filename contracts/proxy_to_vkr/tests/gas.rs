@@ -0,0 +1,296 @@
+//! Gas and message size regression benchmarks over the compiled contract.
+//!
+//! Build the contract and run the benchmarks from the contract directory:
+//! ```text
+//! cargo wasm
+//! cargo test --test gas -- --ignored
+//! ```
+//! `UPDATE_GAS_BASELINE=1` rewrites `gas_baseline.json` with the current measurements.
+//! `PROXY_WASM` overrides the path of the compiled contract.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use ap_valkyrie::lp_staking::{ConfigResponse, QueryMsg as VkrQueryMsg, StakerInfoResponse};
+use ap_valkyrie::{Cw20HookMsg, ExecuteMsg, InstantiateMsg};
+use cosmwasm_schema::serde::{Deserialize, Serialize};
+use cosmwasm_std::{
+    from_binary, to_binary, to_vec, ContractResult, Decimal, QuerierResult, Reply, Response,
+    SubMsgResponse, SubMsgResult, SystemError, SystemResult, Uint128, WasmQuery,
+};
+use cosmwasm_vm::testing::{
+    execute, instantiate, mock_env, mock_info, mock_instance_with_gas_limit, reply, MockApi,
+    MockQuerier, MockStorage,
+};
+use cosmwasm_vm::Instance;
+use cw20::{BalanceResponse, Cw20QueryMsg, Cw20ReceiveMsg};
+
+const GENERATOR: &str = "generator";
+const PAIR: &str = "pair";
+const LP_TOKEN: &str = "lptoken";
+const REWARD_CONTRACT: &str = "rewardcontract";
+const REWARD_TOKEN: &str = "rewardtoken";
+const OWNER: &str = "owner";
+const USER: &str = "user";
+
+/// Balance reported by the staking contract and the reward token
+const BALANCE: u128 = 1_000_000;
+const AMOUNT: u128 = 1_000;
+
+/// LP token balance of the proxy, raised when the staking contract unbonds
+static LP_BALANCE: AtomicU64 = AtomicU64::new(0);
+
+/// Withdraw reply id of the contract
+const WITHDRAW_REPLY_ID: u64 = 1;
+
+const GAS_LIMIT: u64 = 500_000_000_000;
+/// A measurement may grow by this percentage of the baseline before the benchmark fails
+const MAX_REGRESSION_PERCENT: u64 = 5;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "cosmwasm_schema::serde")]
+struct Measurement {
+    /// VM gas used by every execution of the path
+    gas: u64,
+    /// Size of the JSON encoded messages emitted by the path
+    msg_bytes: u64,
+}
+
+enum Step {
+    Execute(&'static str, ExecuteMsg),
+    /// The staking contract unbonds the LP tokens and replies
+    UnbondReply,
+}
+
+type ProxyInstance = Instance<MockApi, MockStorage, MockQuerier>;
+
+fn wasm_path() -> PathBuf {
+    match std::env::var("PROXY_WASM") {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../target/wasm32-unknown-unknown/release/generator_proxy_to_vkr.wasm"),
+    }
+}
+
+fn baseline_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("gas_baseline.json")
+}
+
+fn handle_wasm_query(query: &WasmQuery) -> QuerierResult {
+    let (contract_addr, msg) = match query {
+        WasmQuery::Smart { contract_addr, msg } => (contract_addr, msg),
+        _ => {
+            return SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "only smart queries are mocked".to_string(),
+            })
+        }
+    };
+
+    let response = match contract_addr.as_str() {
+        LP_TOKEN => match from_binary(msg) {
+            Ok(Cw20QueryMsg::Balance { .. }) => to_binary(&BalanceResponse {
+                balance: Uint128::from(LP_BALANCE.load(Ordering::SeqCst)),
+            }),
+            _ => panic!("unexpected token query"),
+        },
+        REWARD_TOKEN => match from_binary(msg) {
+            Ok(Cw20QueryMsg::Balance { .. }) => to_binary(&BalanceResponse {
+                balance: Uint128::new(BALANCE),
+            }),
+            _ => panic!("unexpected token query"),
+        },
+        REWARD_CONTRACT => match from_binary(msg) {
+            Ok(VkrQueryMsg::Config {}) => to_binary(&ConfigResponse {
+                token: REWARD_TOKEN.to_string(),
+                pair: PAIR.to_string(),
+                lp_token: LP_TOKEN.to_string(),
+            }),
+            Ok(VkrQueryMsg::StakerInfo { staker }) => to_binary(&StakerInfoResponse {
+                staker,
+                reward_index: Decimal::zero(),
+                bond_amount: Uint128::new(BALANCE),
+                pending_reward: Uint128::new(BALANCE),
+            }),
+            Err(_) => panic!("unexpected staking query"),
+        },
+        _ => {
+            return SystemResult::Err(SystemError::NoSuchContract {
+                addr: contract_addr.clone(),
+            })
+        }
+    };
+    SystemResult::Ok(ContractResult::Ok(response.unwrap()))
+}
+
+fn setup(wasm: &[u8]) -> ProxyInstance {
+    LP_BALANCE.store(0, Ordering::SeqCst);
+    let mut instance = mock_instance_with_gas_limit(wasm, GAS_LIMIT);
+    instance
+        .with_querier(|querier| {
+            querier.update_wasm(handle_wasm_query);
+            Ok(())
+        })
+        .unwrap();
+
+    let res: Response = instantiate(
+        &mut instance,
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg {
+            generator_contract_addr: GENERATOR.to_string(),
+            pair_addr: PAIR.to_string(),
+            lp_token_addr: LP_TOKEN.to_string(),
+            reward_contract_addr: REWARD_CONTRACT.to_string(),
+            reward_token_addr: REWARD_TOKEN.to_string(),
+            owner: None,
+        },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    instance
+}
+
+/// Runs the steps in order and sums up the gas used and the size of the emitted messages
+fn measure(instance: &mut ProxyInstance, steps: Vec<Step>) -> Measurement {
+    let mut measurement = Measurement {
+        gas: 0,
+        msg_bytes: 0,
+    };
+    for step in steps {
+        let gas_before = instance.get_gas_left();
+        let res: Response = match step {
+            Step::Execute(sender, msg) => {
+                execute(instance, mock_env(), mock_info(sender, &[]), msg).unwrap()
+            }
+            Step::UnbondReply => {
+                LP_BALANCE.fetch_add(AMOUNT as u64, Ordering::SeqCst);
+                reply(
+                    instance,
+                    mock_env(),
+                    Reply {
+                        id: WITHDRAW_REPLY_ID,
+                        result: SubMsgResult::Ok(SubMsgResponse {
+                            events: vec![],
+                            data: None,
+                        }),
+                    },
+                )
+                .unwrap()
+            }
+        };
+        measurement.gas += gas_before - instance.get_gas_left();
+        measurement.msg_bytes += res
+            .messages
+            .iter()
+            .map(|sub_msg| to_vec(&sub_msg.msg).unwrap().len() as u64)
+            .sum::<u64>();
+    }
+    measurement
+}
+
+fn deposit_steps() -> Vec<Step> {
+    vec![Step::Execute(
+        LP_TOKEN,
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: GENERATOR.to_string(),
+            amount: Uint128::new(AMOUNT),
+            msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
+        }),
+    )]
+}
+
+fn measure_all(wasm: &[u8]) -> BTreeMap<String, Measurement> {
+    // the withdraw path includes the reply transferring the unbonded LP tokens
+    let withdraw = vec![
+        Step::Execute(
+            GENERATOR,
+            ExecuteMsg::Withdraw {
+                account: USER.to_string(),
+                amount: Uint128::new(AMOUNT),
+            },
+        ),
+        Step::UnbondReply,
+    ];
+    let update_rewards = vec![Step::Execute(GENERATOR, ExecuteMsg::UpdateRewards {})];
+    let send_rewards = vec![Step::Execute(
+        GENERATOR,
+        ExecuteMsg::SendRewards {
+            account: USER.to_string(),
+            amount: Uint128::new(AMOUNT),
+        },
+    )];
+
+    [
+        ("deposit", deposit_steps()),
+        ("withdraw", withdraw),
+        ("update_rewards", update_rewards),
+        ("send_rewards", send_rewards),
+    ]
+    .into_iter()
+    .map(|(name, steps)| {
+        // every path runs on a freshly instantiated contract, all but deposit after a deposit
+        let mut instance = setup(wasm);
+        if name != "deposit" {
+            measure(&mut instance, deposit_steps());
+        }
+        (name.to_string(), measure(&mut instance, steps))
+    })
+    .collect()
+}
+
+#[test]
+#[ignore = "requires the compiled contract, run `cargo wasm` first"]
+fn gas_does_not_regress() {
+    let wasm = std::fs::read(wasm_path())
+        .unwrap_or_else(|err| panic!("{}: {}, run `cargo wasm`", wasm_path().display(), err));
+    let current = measure_all(&wasm);
+    for (name, measurement) in &current {
+        println!(
+            "{:<16} gas {:>12} msg bytes {:>6}",
+            name, measurement.gas, measurement.msg_bytes
+        );
+    }
+
+    if std::env::var("UPDATE_GAS_BASELINE").is_ok() {
+        let json = serde_json::to_string_pretty(&current).unwrap();
+        std::fs::write(baseline_path(), json + "\n").unwrap();
+        return;
+    }
+
+    let baseline: BTreeMap<String, Measurement> = serde_json::from_str(
+        &std::fs::read_to_string(baseline_path()).unwrap_or_else(|err| {
+            panic!(
+                "{}: {}, run with UPDATE_GAS_BASELINE=1",
+                baseline_path().display(),
+                err
+            )
+        }),
+    )
+    .unwrap();
+
+    let limit = |value: u64| value + value * MAX_REGRESSION_PERCENT / 100;
+    let regressions: Vec<String> = current
+        .iter()
+        .filter_map(|(name, measurement)| {
+            let base = baseline.get(name)?;
+            (measurement.gas > limit(base.gas) || measurement.msg_bytes > limit(base.msg_bytes))
+                .then(|| format!("{}: {:?} exceeds baseline {:?}", name, measurement, base))
+        })
+        .collect();
+    assert!(
+        regressions.is_empty(),
+        "gas regressions, run with UPDATE_GAS_BASELINE=1 if expected:\n{}",
+        regressions.join("\n")
+    );
+
+    let missing: Vec<&String> = current
+        .keys()
+        .filter(|name| !baseline.contains_key(*name))
+        .collect();
+    assert!(
+        missing.is_empty(),
+        "{:?} missing from the baseline, run with UPDATE_GAS_BASELINE=1",
+        missing
+    );
+}
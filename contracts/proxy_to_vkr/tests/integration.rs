@@ -0,0 +1,685 @@
+mod common;
+
+use ap_valkyrie::router::AssetInfo;
+use ap_valkyrie::{
+    ConfigResponse, DepositResponse, ExecuteMsg, HealthResponse, IdleBalanceResponse,
+    InstantiateMsg, PendingTokenResponse, PendingWithdrawalsResponse, ProxyContract, ProxyStatus,
+    QueryMsg, RewardConversionInfo, RewardInfoResponse, RewardResponse, RewardSplitInfo,
+    StreamStateResponse,
+};
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw_multi_test::Executor;
+
+use common::{Suite, OWNER, PAIR, USER_LP_BALANCE};
+
+const REWARD_PER_BLOCK: u128 = 1_000;
+
+#[test]
+fn deposit_accrue_claim_send_withdraw() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+
+    suite.deposit("user1", 100).unwrap();
+    assert_eq!(suite.lp_balance("user1"), USER_LP_BALANCE - 100);
+    assert_eq!(suite.staker_info().bond_amount.u128(), 100);
+
+    let deposit: Uint128 = suite.query_proxy(&QueryMsg::Deposit {}).unwrap();
+    assert_eq!(deposit.u128(), 100);
+
+    // rewards accrue upstream
+    suite.next_blocks(10);
+    let pending: Option<Uint128> = suite.query_proxy(&QueryMsg::PendingToken {}).unwrap();
+    assert_eq!(pending, Some(Uint128::new(10 * REWARD_PER_BLOCK)));
+
+    // the generator claims them into the proxy
+    suite.update_rewards().unwrap();
+    let reward: Uint128 = suite.query_proxy(&QueryMsg::Reward {}).unwrap();
+    assert_eq!(reward.u128(), 10 * REWARD_PER_BLOCK);
+    let pending: Option<Uint128> = suite.query_proxy(&QueryMsg::PendingToken {}).unwrap();
+    assert_eq!(pending, Some(Uint128::zero()));
+
+    // and distributes them
+    suite.send_rewards("user1", 10 * REWARD_PER_BLOCK).unwrap();
+    assert_eq!(suite.reward_balance("user1"), 10 * REWARD_PER_BLOCK);
+    assert_eq!(suite.reward_balance(suite.proxy.clone()), 0);
+
+    // the unbond reply returns the unbonded LP tokens to the user
+    suite.withdraw("user1", 100).unwrap();
+    assert_eq!(suite.lp_balance("user1"), USER_LP_BALANCE);
+    assert_eq!(suite.lp_balance(suite.proxy.clone()), 0);
+    assert_eq!(suite.staker_info().bond_amount.u128(), 0);
+
+    let health: HealthResponse = suite.query_proxy(&QueryMsg::Health {}).unwrap();
+    assert!(health.failed_invariants.is_empty());
+}
+
+#[test]
+fn multiple_users_deposit_and_withdraw() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+
+    suite.deposit("user1", 100).unwrap();
+    suite.next_blocks(10);
+    suite.deposit("user2", 300).unwrap();
+    suite.next_blocks(10);
+
+    // the proxy is a single staker upstream, so it accrues the whole emission
+    suite.update_rewards().unwrap();
+    let reward: Uint128 = suite.query_proxy(&QueryMsg::Reward {}).unwrap();
+    assert_eq!(reward.u128(), 20 * REWARD_PER_BLOCK);
+
+    // partial withdrawals only return the requested amount to the right user
+    suite.withdraw("user2", 120).unwrap();
+    suite.withdraw("user1", 40).unwrap();
+    assert_eq!(suite.lp_balance("user1"), USER_LP_BALANCE - 60);
+    assert_eq!(suite.lp_balance("user2"), USER_LP_BALANCE - 180);
+    assert_eq!(suite.staker_info().bond_amount.u128(), 240);
+
+    let health: HealthResponse = suite.query_proxy(&QueryMsg::Health {}).unwrap();
+    assert_eq!(health.local_bond_amount.u128(), 240);
+    assert!(health.failed_invariants.is_empty());
+}
+
+#[test]
+fn donated_lp_is_restaked_as_surplus() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+
+    suite.deposit("user1", 100).unwrap();
+    suite.donate_lp("user3", 50).unwrap();
+
+    // the withdrawal only transfers what was unbonded
+    suite.withdraw("user1", 100).unwrap();
+    assert_eq!(suite.lp_balance("user1"), USER_LP_BALANCE);
+    assert_eq!(suite.lp_balance(suite.proxy.clone()), 50);
+
+    let idle: IdleBalanceResponse = suite.query_proxy(&QueryMsg::IdleBalance {}).unwrap();
+    assert_eq!(idle.idle_amount.u128(), 50);
+    assert!(idle.held_amount.is_zero());
+    assert_eq!(idle.surplus_amount.u128(), 50);
+
+    // the donation is reported as surplus without failing any invariant
+    let health: HealthResponse = suite.query_proxy(&QueryMsg::Health {}).unwrap();
+    assert_eq!(health.surplus_lp_balance.u128(), 50);
+    assert!(health.failed_invariants.is_empty());
+
+    // the donation is bonded as surplus which doesn't inflate the deposit
+    suite
+        .execute_proxy("user3", &ExecuteMsg::RestakeIdle {})
+        .unwrap_err();
+    suite
+        .execute_proxy(OWNER, &ExecuteMsg::RestakeIdle {})
+        .unwrap();
+    assert_eq!(suite.lp_balance(suite.proxy.clone()), 0);
+    assert_eq!(suite.staker_info().bond_amount.u128(), 50);
+    let deposit: Uint128 = suite.query_proxy(&QueryMsg::Deposit {}).unwrap();
+    assert!(deposit.is_zero());
+
+    let health: HealthResponse = suite.query_proxy(&QueryMsg::Health {}).unwrap();
+    assert!(health.local_bond_amount.is_zero());
+    assert_eq!(health.surplus_bond_amount.u128(), 50);
+    assert!(health.failed_invariants.is_empty());
+
+    // deposits are withdrawn without touching the surplus
+    suite.deposit("user2", 100).unwrap();
+    let deposit: Uint128 = suite.query_proxy(&QueryMsg::Deposit {}).unwrap();
+    assert_eq!(deposit.u128(), 100);
+    suite.withdraw("user2", 40).unwrap();
+    assert_eq!(suite.lp_balance("user2"), USER_LP_BALANCE - 60);
+    assert_eq!(suite.staker_info().bond_amount.u128(), 110);
+
+    // the surplus is not reserved for withdrawals on deprecation
+    suite
+        .execute_proxy(OWNER, &ExecuteMsg::Deprecate {})
+        .unwrap();
+    let deposit: Uint128 = suite.query_proxy(&QueryMsg::Deposit {}).unwrap();
+    assert_eq!(deposit.u128(), 60);
+    suite.withdraw("user2", 60).unwrap();
+    assert_eq!(suite.lp_balance("user2"), USER_LP_BALANCE);
+    assert_eq!(suite.lp_balance(suite.proxy.clone()), 50);
+    let health: HealthResponse = suite.query_proxy(&QueryMsg::Health {}).unwrap();
+    assert_eq!(health.surplus_lp_balance.u128(), 50);
+    assert!(health.failed_invariants.is_empty());
+}
+
+#[test]
+fn withdraw_claims_rewards_when_enabled() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+
+    suite
+        .execute_proxy(
+            OWNER,
+            &ExecuteMsg::UpdateSettings {
+                claim_rewards_on_withdraw: Some(true),
+                unbonding_period: None,
+                hold_lp_on_bond_failure: None,
+                harvest_interval: None,
+                harvest_bounty: None,
+                min_claim_amount: None,
+                reward_stream_period: None,
+            },
+        )
+        .unwrap();
+
+    suite.deposit("user1", 100).unwrap();
+    suite.next_blocks(5);
+    suite.withdraw("user1", 100).unwrap();
+
+    assert_eq!(suite.lp_balance("user1"), USER_LP_BALANCE);
+    assert_eq!(
+        suite.reward_balance(suite.proxy.clone()),
+        5 * REWARD_PER_BLOCK
+    );
+    assert!(suite.staker_info().pending_reward.is_zero());
+}
+
+#[test]
+fn queued_withdrawals_are_paid_after_unbonding_period() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+
+    suite
+        .execute_proxy(
+            OWNER,
+            &ExecuteMsg::UpdateSettings {
+                claim_rewards_on_withdraw: None,
+                unbonding_period: Some(100),
+                hold_lp_on_bond_failure: None,
+                harvest_interval: None,
+                harvest_bounty: None,
+                min_claim_amount: None,
+                reward_stream_period: None,
+            },
+        )
+        .unwrap();
+
+    suite.deposit("user1", 100).unwrap();
+    suite.withdraw("user1", 100).unwrap();
+
+    // the unbonded LP tokens wait in the proxy and are not restakable
+    assert_eq!(suite.lp_balance("user1"), USER_LP_BALANCE - 100);
+    assert_eq!(suite.lp_balance(suite.proxy.clone()), 100);
+    suite
+        .execute_proxy(OWNER, &ExecuteMsg::RestakeIdle {})
+        .unwrap_err();
+
+    let claim_msg = ExecuteMsg::ClaimUnbonded {
+        account: "user1".to_string(),
+    };
+    suite.execute_proxy("user2", &claim_msg).unwrap_err();
+
+    // 20 blocks of 5 seconds
+    suite.next_blocks(20);
+    let pending: PendingWithdrawalsResponse = suite
+        .query_proxy(&QueryMsg::PendingWithdrawals {
+            account: "user1".to_string(),
+        })
+        .unwrap();
+    assert_eq!(pending.claimable_amount.u128(), 100);
+
+    suite.execute_proxy("user2", &claim_msg).unwrap();
+    assert_eq!(suite.lp_balance("user1"), USER_LP_BALANCE);
+    assert_eq!(suite.lp_balance(suite.proxy.clone()), 0);
+}
+
+#[test]
+fn instantiate_rejects_foreign_reward_token() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+
+    let err = suite
+        .app
+        .instantiate_contract(
+            suite.proxy_code_id,
+            Addr::unchecked(OWNER),
+            &InstantiateMsg {
+                generator_contract_addr: suite.generator.to_string(),
+                pair_addr: PAIR.to_string(),
+                lp_token_addr: suite.lp_token.to_string(),
+                reward_contract_addr: suite.staking.to_string(),
+                reward_token_addr: suite.lp_token.to_string(),
+                owner: None,
+            },
+            &[],
+            "Generator proxy to VKR",
+            None,
+        )
+        .unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains("Reward token mismatch"));
+}
+
+#[test]
+fn proxy_contract_helpers() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+    let proxy = ProxyContract(suite.proxy.clone());
+
+    let config = proxy.query_config(&suite.app.wrap()).unwrap();
+    assert_eq!(config.generator_contract_addr, suite.generator.to_string());
+    assert_eq!(
+        proxy.query_reward_info(&suite.app.wrap()).unwrap(),
+        suite.reward_token
+    );
+    assert_eq!(
+        proxy.query_reward_info_v2(&suite.app.wrap()).unwrap(),
+        RewardInfoResponse {
+            token: suite.reward_token.clone(),
+            reward_contract: suite.staking.clone(),
+        }
+    );
+
+    // the generator stand-in deposits and withdraws through the helpers
+    suite.deposit("user1", 100).unwrap();
+    assert_eq!(
+        proxy.query_deposit(&suite.app.wrap()).unwrap(),
+        Uint128::new(100)
+    );
+    assert_eq!(
+        proxy.query_deposit_v2(&suite.app.wrap()).unwrap(),
+        DepositResponse {
+            amount: Uint128::new(100),
+            lp_token: suite.lp_token.clone(),
+        }
+    );
+
+    suite.next_blocks(10);
+    assert_eq!(
+        proxy.query_pending_token(&suite.app.wrap()).unwrap(),
+        Some(Uint128::new(10 * REWARD_PER_BLOCK))
+    );
+    assert_eq!(
+        proxy.query_pending_token_v2(&suite.app.wrap()).unwrap(),
+        PendingTokenResponse {
+            amount: Uint128::new(10 * REWARD_PER_BLOCK),
+            token: suite.reward_token.clone(),
+        }
+    );
+
+    let generator = suite.generator.clone();
+    suite
+        .app
+        .execute(generator.clone(), proxy.update_rewards_msg().unwrap())
+        .unwrap();
+    assert_eq!(
+        proxy.query_reward(&suite.app.wrap()).unwrap(),
+        Uint128::new(10 * REWARD_PER_BLOCK)
+    );
+    assert_eq!(
+        proxy.query_reward_v2(&suite.app.wrap()).unwrap(),
+        RewardResponse {
+            amount: Uint128::new(10 * REWARD_PER_BLOCK),
+            token: suite.reward_token.clone(),
+        }
+    );
+
+    // messages built for another sender are still checked by the proxy
+    let user = Addr::unchecked("user1");
+    suite
+        .app
+        .execute(user.clone(), proxy.update_rewards_msg().unwrap())
+        .unwrap_err();
+
+    suite
+        .app
+        .execute(
+            generator,
+            proxy
+                .send_rewards_msg(user.clone(), Uint128::new(10 * REWARD_PER_BLOCK))
+                .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(suite.reward_balance(user), 10 * REWARD_PER_BLOCK);
+
+    suite.withdraw("user1", 100).unwrap();
+    assert_eq!(
+        proxy.query_deposit(&suite.app.wrap()).unwrap(),
+        Uint128::zero()
+    );
+    assert!(proxy
+        .query_health(&suite.app.wrap())
+        .unwrap()
+        .failed_invariants
+        .is_empty());
+}
+
+#[test]
+fn staking_position_migrates_to_new_staking_contract() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+    suite.deposit("user1", 100).unwrap();
+    suite.deposit("user2", 150).unwrap();
+    suite.next_blocks(10);
+
+    let old_staking = suite.staking.clone();
+    let new_staking = suite.instantiate_staking(2 * REWARD_PER_BLOCK);
+    let migrate_msg = ExecuteMsg::MigrateStakingContract {
+        new_reward_contract: new_staking.to_string(),
+    };
+
+    let generator = suite.generator.to_string();
+    suite.execute_proxy(&generator, &migrate_msg).unwrap_err();
+    suite.execute_proxy(OWNER, &migrate_msg).unwrap();
+
+    // the whole position and the pending rewards left the old staking contract
+    let old_position = suite.staker_info_at(&old_staking);
+    assert_eq!(old_position.bond_amount, Uint128::zero());
+    assert_eq!(old_position.pending_reward, Uint128::zero());
+    assert_eq!(
+        suite.reward_balance(suite.proxy.clone()),
+        10 * REWARD_PER_BLOCK
+    );
+    assert_eq!(
+        suite.staker_info_at(&new_staking).bond_amount,
+        Uint128::new(250)
+    );
+    assert_eq!(suite.lp_balance(suite.proxy.clone()), 0);
+
+    let config: ConfigResponse = suite.query_proxy(&QueryMsg::Config {}).unwrap();
+    assert_eq!(config.reward_contract_addr, new_staking.to_string());
+    let health: HealthResponse = suite.query_proxy(&QueryMsg::Health {}).unwrap();
+    assert!(health.failed_invariants.is_empty());
+
+    // rewards accrue in the new staking contract and withdrawals are paid from it
+    suite.staking = new_staking.clone();
+    suite.next_blocks(5);
+    let pending: Option<Uint128> = suite.query_proxy(&QueryMsg::PendingToken {}).unwrap();
+    assert_eq!(pending, Some(Uint128::new(10 * REWARD_PER_BLOCK)));
+
+    suite.withdraw("user1", 100).unwrap();
+    assert_eq!(suite.lp_balance("user1"), USER_LP_BALANCE);
+    assert_eq!(suite.staker_info().bond_amount, Uint128::new(150));
+}
+
+#[test]
+fn deprecated_proxy_pays_withdrawals_from_held_lp_tokens() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+    suite.deposit("user1", 100).unwrap();
+    suite.deposit("user2", 150).unwrap();
+    suite.next_blocks(10);
+
+    let generator = suite.generator.to_string();
+    suite
+        .execute_proxy(&generator, &ExecuteMsg::Deprecate {})
+        .unwrap_err();
+    suite
+        .execute_proxy(OWNER, &ExecuteMsg::Deprecate {})
+        .unwrap();
+
+    // the final rewards were claimed and the whole position is held by the proxy
+    let position = suite.staker_info();
+    assert_eq!(position.bond_amount, Uint128::zero());
+    assert_eq!(position.pending_reward, Uint128::zero());
+    assert_eq!(
+        suite.reward_balance(suite.proxy.clone()),
+        10 * REWARD_PER_BLOCK
+    );
+    assert_eq!(suite.lp_balance(suite.proxy.clone()), 250);
+    // the generator keeps seeing the deposited LP tokens as the pool supply
+    let deposit: Uint128 = suite.query_proxy(&QueryMsg::Deposit {}).unwrap();
+    assert_eq!(deposit.u128(), 250);
+
+    let status: ProxyStatus = suite.query_proxy(&QueryMsg::Status {}).unwrap();
+    assert_eq!(status, ProxyStatus::Deprecated);
+    let health: HealthResponse = suite.query_proxy(&QueryMsg::Health {}).unwrap();
+    assert_eq!(health.status, ProxyStatus::Deprecated);
+    assert!(health.deposits_paused);
+    assert!(health.reward_claims_paused);
+    assert!(health.failed_invariants.is_empty());
+
+    suite.deposit("user1", 10).unwrap_err();
+
+    suite.withdraw("user1", 100).unwrap();
+    assert_eq!(suite.lp_balance("user1"), USER_LP_BALANCE);
+    assert_eq!(suite.lp_balance(suite.proxy.clone()), 150);
+    let deposit: Uint128 = suite.query_proxy(&QueryMsg::Deposit {}).unwrap();
+    assert_eq!(deposit.u128(), 150);
+    suite.withdraw("user2", 150).unwrap();
+    assert_eq!(suite.lp_balance("user2"), USER_LP_BALANCE);
+    assert_eq!(suite.lp_balance(suite.proxy.clone()), 0);
+}
+
+#[test]
+fn keepers_harvest_idle_pools_for_a_bounty() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+    suite.deposit("user1", 100).unwrap();
+    suite
+        .execute_proxy(
+            OWNER,
+            &ExecuteMsg::UpdateSettings {
+                claim_rewards_on_withdraw: None,
+                unbonding_period: None,
+                hold_lp_on_bond_failure: None,
+                harvest_interval: Some(60),
+                harvest_bounty: Some(Decimal::percent(1)),
+                min_claim_amount: None,
+                reward_stream_period: None,
+            },
+        )
+        .unwrap();
+    suite.next_blocks(10);
+
+    suite
+        .execute_proxy("keeper", &ExecuteMsg::Harvest {})
+        .unwrap();
+    assert_eq!(suite.reward_balance("keeper"), REWARD_PER_BLOCK / 10);
+    assert_eq!(
+        suite.reward_balance(suite.proxy.clone()),
+        10 * REWARD_PER_BLOCK - REWARD_PER_BLOCK / 10
+    );
+    assert_eq!(suite.staker_info().pending_reward, Uint128::zero());
+
+    // 5 seconds per block, the next harvest is possible 12 blocks later
+    suite.next_blocks(11);
+    suite
+        .execute_proxy("keeper", &ExecuteMsg::Harvest {})
+        .unwrap_err();
+    suite.next_blocks(1);
+    suite
+        .execute_proxy("keeper", &ExecuteMsg::Harvest {})
+        .unwrap();
+    assert_eq!(
+        suite.reward_balance("keeper"),
+        REWARD_PER_BLOCK / 10 + 12 * REWARD_PER_BLOCK / 100
+    );
+}
+
+#[test]
+fn streamed_rewards_are_released_linearly() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+    suite.deposit("user1", 100).unwrap();
+    suite
+        .execute_proxy(
+            OWNER,
+            &ExecuteMsg::UpdateSettings {
+                claim_rewards_on_withdraw: None,
+                unbonding_period: None,
+                hold_lp_on_bond_failure: None,
+                harvest_interval: None,
+                harvest_bounty: None,
+                min_claim_amount: None,
+                reward_stream_period: Some(100),
+            },
+        )
+        .unwrap();
+    suite.next_blocks(10);
+
+    // the claimed rewards are released over 100 seconds, 20 blocks
+    suite.update_rewards().unwrap();
+    assert_eq!(
+        suite.reward_balance(suite.proxy.clone()),
+        10 * REWARD_PER_BLOCK
+    );
+    let reward: Uint128 = suite.query_proxy(&QueryMsg::Reward {}).unwrap();
+    assert_eq!(reward, Uint128::zero());
+
+    suite.next_blocks(10);
+    let reward: Uint128 = suite.query_proxy(&QueryMsg::Reward {}).unwrap();
+    assert_eq!(reward.u128(), 5 * REWARD_PER_BLOCK);
+    suite.send_rewards("user1", 5 * REWARD_PER_BLOCK).unwrap();
+    let reward: Uint128 = suite.query_proxy(&QueryMsg::Reward {}).unwrap();
+    assert_eq!(reward, Uint128::zero());
+
+    // the rewards still locked are released together with the next claim
+    suite.update_rewards().unwrap();
+    let stream_state: StreamStateResponse = suite.query_proxy(&QueryMsg::StreamState {}).unwrap();
+    assert_eq!(
+        stream_state.locked_amount.u128(),
+        5 * REWARD_PER_BLOCK + 10 * REWARD_PER_BLOCK
+    );
+
+    suite.next_blocks(20);
+    let reward: Uint128 = suite.query_proxy(&QueryMsg::Reward {}).unwrap();
+    assert_eq!(reward.u128(), 15 * REWARD_PER_BLOCK);
+    suite.send_rewards("user1", 15 * REWARD_PER_BLOCK).unwrap();
+    assert_eq!(suite.reward_balance("user1"), 20 * REWARD_PER_BLOCK);
+}
+
+#[test]
+fn rewards_are_converted_before_distribution() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+    let (router, astro) = suite.instantiate_router(Decimal::percent(50), Decimal::percent(1));
+    let conversion_msg =
+        |max_spread: Decimal, belief_price: Option<Decimal>| ExecuteMsg::UpdateRewardConversion {
+            conversion: Some(RewardConversionInfo {
+                router: router.to_string(),
+                route: vec![AssetInfo::Token {
+                    contract_addr: astro.clone(),
+                }],
+                max_spread,
+                belief_price,
+            }),
+        };
+    suite
+        .execute_proxy(OWNER, &conversion_msg(Decimal::percent(2), None))
+        .unwrap();
+    let reward_token: Addr = suite.query_proxy(&QueryMsg::RewardInfo {}).unwrap();
+    assert_eq!(reward_token, astro);
+
+    suite.deposit("user1", 100).unwrap();
+    suite.next_blocks(10);
+    let pending: Option<Uint128> = suite.query_proxy(&QueryMsg::PendingToken {}).unwrap();
+    assert_eq!(pending, Some(Uint128::new(5 * REWARD_PER_BLOCK)));
+
+    // the claimed rewards are swapped at the router rate
+    suite.update_rewards().unwrap();
+    assert_eq!(suite.reward_balance(suite.proxy.clone()), 0);
+    let reward: Uint128 = suite.query_proxy(&QueryMsg::Reward {}).unwrap();
+    assert_eq!(reward.u128(), 5 * REWARD_PER_BLOCK);
+    suite.send_rewards("user1", 5 * REWARD_PER_BLOCK).unwrap();
+    assert_eq!(suite.token_balance(&astro, "user1"), 5 * REWARD_PER_BLOCK);
+
+    // swaps over the max spread keep the rewards in the proxy without failing the reward update
+    suite
+        .execute_proxy(OWNER, &conversion_msg(Decimal::permille(5), None))
+        .unwrap();
+    suite.next_blocks(10);
+    suite.update_rewards().unwrap();
+    assert_eq!(
+        suite.reward_balance(suite.proxy.clone()),
+        10 * REWARD_PER_BLOCK
+    );
+    let reward: Uint128 = suite.query_proxy(&QueryMsg::Reward {}).unwrap();
+    assert_eq!(reward, Uint128::zero());
+    let pending: Option<Uint128> = suite.query_proxy(&QueryMsg::PendingToken {}).unwrap();
+    assert_eq!(pending, Some(Uint128::new(5 * REWARD_PER_BLOCK)));
+
+    // swaps returning less than the belief price minus the max spread keep the rewards as well
+    suite
+        .execute_proxy(
+            OWNER,
+            &conversion_msg(Decimal::percent(2), Some(Decimal::percent(60))),
+        )
+        .unwrap();
+    suite.update_rewards().unwrap();
+    assert_eq!(
+        suite.reward_balance(suite.proxy.clone()),
+        10 * REWARD_PER_BLOCK
+    );
+
+    // and the next reward update converts them even without new rewards to claim
+    suite
+        .execute_proxy(
+            OWNER,
+            &conversion_msg(Decimal::percent(2), Some(Decimal::percent(50))),
+        )
+        .unwrap();
+    suite.update_rewards().unwrap();
+    assert_eq!(suite.reward_balance(suite.proxy.clone()), 0);
+    let reward: Uint128 = suite.query_proxy(&QueryMsg::Reward {}).unwrap();
+    assert_eq!(reward.u128(), 5 * REWARD_PER_BLOCK);
+
+    // the distributed token is locked while LP tokens are deposited
+    suite
+        .execute_proxy(
+            OWNER,
+            &ExecuteMsg::UpdateRewardConversion { conversion: None },
+        )
+        .unwrap_err();
+}
+
+#[test]
+fn claimed_rewards_are_split_among_beneficiaries() {
+    let mut suite = Suite::new(REWARD_PER_BLOCK);
+    suite.deposit("user1", 100).unwrap();
+    suite
+        .execute_proxy(
+            OWNER,
+            &ExecuteMsg::UpdateSettings {
+                claim_rewards_on_withdraw: None,
+                unbonding_period: None,
+                hold_lp_on_bond_failure: None,
+                harvest_interval: None,
+                harvest_bounty: None,
+                min_claim_amount: None,
+                reward_stream_period: Some(100),
+            },
+        )
+        .unwrap();
+    let generator = suite.generator.to_string();
+    suite
+        .execute_proxy(
+            OWNER,
+            &ExecuteMsg::UpdateRewardSplits {
+                splits: vec![
+                    RewardSplitInfo {
+                        recipient: generator,
+                        weight: Decimal::percent(75),
+                    },
+                    RewardSplitInfo {
+                        recipient: "treasury".to_string(),
+                        weight: Decimal::percent(25),
+                    },
+                ],
+            },
+        )
+        .unwrap();
+    suite.next_blocks(10);
+    let pending: Option<Uint128> = suite.query_proxy(&QueryMsg::PendingToken {}).unwrap();
+    assert_eq!(pending, Some(Uint128::new(10 * REWARD_PER_BLOCK * 3 / 4)));
+
+    // the beneficiary share is claimable at once, only the generator share is streamed
+    suite.update_rewards().unwrap();
+    let accrued: Uint128 = suite
+        .query_proxy(&QueryMsg::AccruedSplit {
+            recipient: "treasury".to_string(),
+        })
+        .unwrap();
+    assert_eq!(accrued.u128(), 10 * REWARD_PER_BLOCK / 4);
+    let stream_state: StreamStateResponse = suite.query_proxy(&QueryMsg::StreamState {}).unwrap();
+    assert_eq!(
+        stream_state.locked_amount.u128(),
+        10 * REWARD_PER_BLOCK * 3 / 4
+    );
+
+    suite
+        .execute_proxy("treasury", &ExecuteMsg::ClaimSplit {})
+        .unwrap();
+    assert_eq!(suite.reward_balance("treasury"), 10 * REWARD_PER_BLOCK / 4);
+    suite
+        .execute_proxy("treasury", &ExecuteMsg::ClaimSplit {})
+        .unwrap_err();
+
+    suite.next_blocks(20);
+    let reward: Uint128 = suite.query_proxy(&QueryMsg::Reward {}).unwrap();
+    assert_eq!(reward.u128(), 10 * REWARD_PER_BLOCK * 3 / 4);
+    suite
+        .send_rewards("user1", 10 * REWARD_PER_BLOCK * 3 / 4)
+        .unwrap();
+    assert_eq!(suite.reward_balance(suite.proxy.clone()), 0);
+}
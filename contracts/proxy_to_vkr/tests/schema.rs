@@ -0,0 +1,35 @@
+use std::path::Path;
+
+use ap_valkyrie::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use cosmwasm_schema::generate_api;
+
+/// Fails when the committed schema does not match the messages anymore.
+/// Regenerate it with `cargo schema` from the contract directory.
+#[test]
+fn committed_schema_is_up_to_date() {
+    let api = generate_api! {
+        instantiate: InstantiateMsg,
+        query: QueryMsg,
+        execute: ExecuteMsg,
+        migrate: MigrateMsg,
+    }
+    .render();
+    let generated = api.to_string().unwrap();
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("schema")
+        .join(concat!(env!("CARGO_PKG_NAME"), ".json"));
+    let committed = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "{}: {}, run `cargo schema` to generate it",
+            path.display(),
+            err
+        )
+    });
+
+    assert!(
+        committed.trim_end() == generated.trim_end(),
+        "{} is out of date, run `cargo schema` and commit the result",
+        path.display()
+    );
+}
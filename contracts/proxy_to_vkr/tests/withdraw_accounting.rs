@@ -0,0 +1,164 @@
+mod common;
+
+use std::collections::HashMap;
+
+use ap_valkyrie::{ExecuteMsg, QueryMsg};
+use cosmwasm_std::Uint128;
+use proptest::prelude::*;
+
+use common::{Suite, OWNER, REWARD_SUPPLY, USERS, USER_LP_BALANCE};
+
+const REWARD_PER_BLOCK: u128 = 1_234;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Deposit {
+        user: usize,
+        amount: u128,
+    },
+    /// Withdraws the given percentage of the user deposit
+    Withdraw {
+        user: usize,
+        percent: u128,
+    },
+    Donate {
+        user: usize,
+        amount: u128,
+    },
+    /// Bonds the donated LP tokens as surplus
+    RestakeIdle,
+    NextBlocks(u64),
+    /// Claims rewards and sends everything the proxy holds to the user
+    ClaimRewards {
+        user: usize,
+    },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let user = 0..USERS.len();
+    prop_oneof![
+        (user.clone(), 1..10_000u128).prop_map(|(user, amount)| Op::Deposit { user, amount }),
+        (user.clone(), 1..=100u128).prop_map(|(user, percent)| Op::Withdraw { user, percent }),
+        (user.clone(), 1..1_000u128).prop_map(|(user, amount)| Op::Donate { user, amount }),
+        Just(Op::RestakeIdle),
+        (1..50u64).prop_map(Op::NextBlocks),
+        user.prop_map(|user| Op::ClaimRewards { user }),
+    ]
+}
+
+/// Expected balances tracked outside of the contracts
+#[derive(Default)]
+struct Model {
+    deposited: HashMap<usize, u128>,
+    donated: HashMap<usize, u128>,
+    restaked: u128,
+}
+
+impl Model {
+    fn total_deposited(&self) -> u128 {
+        self.deposited.values().sum()
+    }
+
+    fn total_donated(&self) -> u128 {
+        self.donated.values().sum()
+    }
+
+    fn idle_donated(&self) -> u128 {
+        self.total_donated() - self.restaked
+    }
+}
+
+fn apply(suite: &mut Suite, model: &mut Model, op: &Op) {
+    match *op {
+        Op::Deposit { user, amount } => {
+            suite.deposit(USERS[user], amount).unwrap();
+            *model.deposited.entry(user).or_default() += amount;
+        }
+        Op::Withdraw { user, percent } => {
+            let deposited = model.deposited.entry(user).or_default();
+            let amount = *deposited * percent / 100;
+            if amount > 0 {
+                suite.withdraw(USERS[user], amount).unwrap();
+                *deposited -= amount;
+            }
+        }
+        Op::Donate { user, amount } => {
+            suite.donate_lp(USERS[user], amount).unwrap();
+            *model.donated.entry(user).or_default() += amount;
+        }
+        Op::RestakeIdle => {
+            let res = suite.execute_proxy(OWNER, &ExecuteMsg::RestakeIdle {});
+            if model.idle_donated() > 0 {
+                res.unwrap();
+                model.restaked = model.total_donated();
+            } else {
+                res.unwrap_err();
+            }
+        }
+        Op::NextBlocks(blocks) => suite.next_blocks(blocks),
+        Op::ClaimRewards { user } => {
+            suite.update_rewards().unwrap();
+            let reward: Uint128 = suite.query_proxy(&QueryMsg::Reward {}).unwrap();
+            if !reward.is_zero() {
+                suite.send_rewards(USERS[user], reward.u128()).unwrap();
+            }
+        }
+    }
+}
+
+fn check_invariants(suite: &Suite, model: &Model) {
+    // no user ever receives LP tokens of another user or donated LP tokens
+    for (user, name) in USERS.iter().enumerate() {
+        let deposited = model.deposited.get(&user).copied().unwrap_or_default();
+        let donated = model.donated.get(&user).copied().unwrap_or_default();
+        assert_eq!(
+            suite.lp_balance(*name),
+            USER_LP_BALANCE - deposited - donated,
+            "LP balance of {}",
+            name
+        );
+    }
+
+    // deposits and restaked donations are bonded upstream, the other donations stay idle in the
+    // proxy and only deposits are reported to the generator
+    assert_eq!(
+        suite.staker_info().bond_amount.u128(),
+        model.total_deposited() + model.restaked
+    );
+    assert_eq!(suite.lp_balance(suite.proxy.clone()), model.idle_donated());
+    let deposit: Uint128 = suite.query_proxy(&QueryMsg::Deposit {}).unwrap();
+    assert_eq!(deposit.u128(), model.total_deposited());
+
+    // the total amount of LP tokens is conserved
+    let total_lp: u128 = USERS
+        .iter()
+        .map(|user| suite.lp_balance(*user))
+        .sum::<u128>()
+        + suite.lp_balance(suite.proxy.clone())
+        + suite.lp_balance(suite.staking.clone());
+    assert_eq!(total_lp, USER_LP_BALANCE * USERS.len() as u128);
+
+    // reward tokens are conserved as well
+    let total_rewards: u128 = USERS
+        .iter()
+        .map(|user| suite.reward_balance(*user))
+        .sum::<u128>()
+        + suite.reward_balance(suite.proxy.clone())
+        + suite.reward_balance(suite.staking.clone());
+    assert_eq!(total_rewards, REWARD_SUPPLY);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn withdraw_accounting(ops in prop::collection::vec(op(), 1..30)) {
+        let mut suite = Suite::new(REWARD_PER_BLOCK);
+        let mut model = Model::default();
+
+        for op in &ops {
+            apply(&mut suite, &mut model, op);
+            check_invariants(&suite, &model);
+        }
+    }
+}
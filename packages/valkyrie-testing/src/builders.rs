@@ -0,0 +1,87 @@
+use ap_valkyrie::InstantiateMsg;
+use cosmwasm_std::Addr;
+
+use crate::ledger::{AssetInfo, PairInfo, PairType};
+
+pub const GENERATOR: &str = "generator0000";
+pub const PAIR: &str = "pair0000";
+pub const LP_TOKEN: &str = "vkrust0000";
+pub const REWARD_CONTRACT: &str = "reward0000";
+pub const REWARD_TOKEN: &str = "vkr0000";
+
+/// Builds a proxy [`InstantiateMsg`] which matches the default [`crate::Ledger`] state
+pub struct ProxyInstantiateMsgBuilder {
+    msg: InstantiateMsg,
+}
+
+impl Default for ProxyInstantiateMsgBuilder {
+    fn default() -> Self {
+        ProxyInstantiateMsgBuilder {
+            msg: InstantiateMsg {
+                generator_contract_addr: GENERATOR.to_string(),
+                pair_addr: PAIR.to_string(),
+                lp_token_addr: LP_TOKEN.to_string(),
+                reward_contract_addr: REWARD_CONTRACT.to_string(),
+                reward_token_addr: REWARD_TOKEN.to_string(),
+                owner: None,
+            },
+        }
+    }
+}
+
+impl ProxyInstantiateMsgBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn generator(mut self, generator: &str) -> Self {
+        self.msg.generator_contract_addr = generator.to_string();
+        self
+    }
+
+    pub fn pair(mut self, pair: &str) -> Self {
+        self.msg.pair_addr = pair.to_string();
+        self
+    }
+
+    pub fn lp_token(mut self, lp_token: &str) -> Self {
+        self.msg.lp_token_addr = lp_token.to_string();
+        self
+    }
+
+    pub fn reward_contract(mut self, reward_contract: &str) -> Self {
+        self.msg.reward_contract_addr = reward_contract.to_string();
+        self
+    }
+
+    pub fn reward_token(mut self, reward_token: &str) -> Self {
+        self.msg.reward_token_addr = reward_token.to_string();
+        self
+    }
+
+    pub fn owner(mut self, owner: &str) -> Self {
+        self.msg.owner = Some(owner.to_string());
+        self
+    }
+
+    pub fn build(self) -> InstantiateMsg {
+        self.msg
+    }
+}
+
+/// Returns the info of an XYK pair between two CW20 tokens
+pub fn pair_info(pair: &str, lp_token: &str, token_a: &str, token_b: &str) -> PairInfo {
+    PairInfo {
+        asset_infos: vec![
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked(token_a),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked(token_b),
+            },
+        ],
+        contract_addr: Addr::unchecked(pair),
+        liquidity_token: Addr::unchecked(lp_token),
+        pair_type: PairType::Xyk {},
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Uint128};
+
+use crate::builders::{LP_TOKEN, PAIR, REWARD_CONTRACT, REWARD_TOKEN};
+
+/// The config reported by a Valkyrie LP staking contract
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StakingConfig {
+    pub token: String,
+    pub pair: String,
+    pub lp_token: String,
+}
+
+/// A staker position in a Valkyrie LP staking contract
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StakerPosition {
+    pub bond_amount: Uint128,
+    pub pending_reward: Uint128,
+}
+
+/// Mirrors the Astroport `AssetInfo` wire format
+#[cw_serde]
+pub enum AssetInfo {
+    Token { contract_addr: Addr },
+    NativeToken { denom: String },
+}
+
+/// Mirrors the Astroport `PairType` wire format
+#[cw_serde]
+pub enum PairType {
+    Xyk {},
+    Stable {},
+    Custom(String),
+}
+
+/// Mirrors the Astroport `PairInfo` wire format
+#[cw_serde]
+pub struct PairInfo {
+    pub asset_infos: Vec<AssetInfo>,
+    pub contract_addr: Addr,
+    pub liquidity_token: Addr,
+    pub pair_type: PairType,
+}
+
+/// Mirrors the Astroport `Asset` wire format
+#[cw_serde]
+pub struct PoolAsset {
+    pub info: AssetInfo,
+    pub amount: Uint128,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Pool {
+    pub info: PairInfo,
+    pub assets: Vec<PoolAsset>,
+    pub total_share: Uint128,
+}
+
+/// State of the mocked contracts, keyed by contract address
+#[derive(Clone, Debug)]
+pub struct Ledger {
+    /// token -> holder -> balance
+    pub(crate) balances: HashMap<String, HashMap<String, Uint128>>,
+    /// staking contract -> staker -> position
+    pub(crate) stakers: HashMap<String, HashMap<String, StakerPosition>>,
+    pub(crate) staking_configs: HashMap<String, StakingConfig>,
+    pub(crate) pools: HashMap<String, Pool>,
+}
+
+impl Default for Ledger {
+    /// The default ledger knows the staking contract and the tokens used by
+    /// [`crate::ProxyInstantiateMsgBuilder`]
+    fn default() -> Self {
+        let mut ledger = Ledger {
+            balances: HashMap::new(),
+            stakers: HashMap::new(),
+            staking_configs: HashMap::new(),
+            pools: HashMap::new(),
+        };
+        for token in [LP_TOKEN, REWARD_TOKEN] {
+            ledger.balances.insert(token.to_string(), HashMap::new());
+        }
+        ledger.set_staking_config(
+            REWARD_CONTRACT,
+            StakingConfig {
+                token: REWARD_TOKEN.to_string(),
+                pair: PAIR.to_string(),
+                lp_token: LP_TOKEN.to_string(),
+            },
+        );
+        ledger
+    }
+}
+
+impl Ledger {
+    /// Replaces every CW20 balance
+    pub fn set_balances(&mut self, balances: &[(&String, &[(&String, &Uint128)])]) {
+        self.balances = balances
+            .iter()
+            .map(|(token, holders)| {
+                let holders = holders
+                    .iter()
+                    .map(|(holder, amount)| (holder.to_string(), **amount))
+                    .collect();
+                (token.to_string(), holders)
+            })
+            .collect();
+    }
+
+    pub fn set_balance(&mut self, token: &str, holder: &str, amount: Uint128) {
+        self.balances
+            .entry(token.to_string())
+            .or_default()
+            .insert(holder.to_string(), amount);
+    }
+
+    pub fn balance(&self, token: &str, holder: &str) -> Uint128 {
+        self.balances
+            .get(token)
+            .and_then(|holders| holders.get(holder))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn mint(&mut self, token: &str, holder: &str, amount: Uint128) {
+        let balance = self.balance(token, holder);
+        self.set_balance(token, holder, balance + amount);
+    }
+
+    /// Moves tokens between holders, panics if the sender balance is too low
+    pub fn transfer(&mut self, token: &str, from: &str, to: &str, amount: Uint128) {
+        let balance = self.balance(token, from);
+        self.set_balance(token, from, balance.checked_sub(amount).unwrap());
+        self.mint(token, to, amount);
+    }
+
+    pub fn set_staking_config(&mut self, staking_contract: &str, config: StakingConfig) {
+        self.staking_configs
+            .insert(staking_contract.to_string(), config);
+    }
+
+    pub fn staking_config(&self, staking_contract: &str) -> Option<&StakingConfig> {
+        self.staking_configs.get(staking_contract)
+    }
+
+    pub fn set_staker(&mut self, staking_contract: &str, staker: &str, position: StakerPosition) {
+        self.stakers
+            .entry(staking_contract.to_string())
+            .or_default()
+            .insert(staker.to_string(), position);
+    }
+
+    pub fn staker(&self, staking_contract: &str, staker: &str) -> StakerPosition {
+        self.stakers
+            .get(staking_contract)
+            .and_then(|stakers| stakers.get(staker))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Increases the staker bond as if LP tokens were sent with a `Bond` hook
+    pub fn bond(&mut self, staking_contract: &str, staker: &str, amount: Uint128) {
+        let mut position = self.staker(staking_contract, staker);
+        position.bond_amount += amount;
+        self.set_staker(staking_contract, staker, position);
+    }
+
+    /// Decreases the staker bond and credits the LP tokens to the staker as `Unbond` does
+    pub fn unbond(&mut self, staking_contract: &str, staker: &str, amount: Uint128) {
+        let mut position = self.staker(staking_contract, staker);
+        position.bond_amount = position.bond_amount.checked_sub(amount).unwrap();
+        self.set_staker(staking_contract, staker, position);
+
+        let lp_token = self
+            .staking_config(staking_contract)
+            .unwrap()
+            .lp_token
+            .clone();
+        self.mint(&lp_token, staker, amount);
+    }
+
+    /// Accrues rewards for the staker
+    pub fn accrue(&mut self, staking_contract: &str, staker: &str, amount: Uint128) {
+        let mut position = self.staker(staking_contract, staker);
+        position.pending_reward += amount;
+        self.set_staker(staking_contract, staker, position);
+    }
+
+    /// Pays out the pending rewards of the staker as `Withdraw` does
+    pub fn claim(&mut self, staking_contract: &str, staker: &str) {
+        let mut position = self.staker(staking_contract, staker);
+        let amount = std::mem::take(&mut position.pending_reward);
+        self.set_staker(staking_contract, staker, position);
+
+        let token = self.staking_config(staking_contract).unwrap().token.clone();
+        self.mint(&token, staker, amount);
+    }
+
+    /// Registers a pair answering the `pair` and `pool` queries
+    pub fn set_pair(&mut self, info: PairInfo, assets: Vec<PoolAsset>, total_share: Uint128) {
+        self.pools.insert(
+            info.contract_addr.to_string(),
+            Pool {
+                info,
+                assets,
+                total_share,
+            },
+        );
+    }
+}
@@ -0,0 +1,15 @@
+//! Test helpers shared by the generator proxy contracts.
+//!
+//! [`WasmMockQuerier`] answers CW20, Valkyrie LP staking and Astroport pair queries from a
+//! [`Ledger`] which tests can mutate between contract calls, e.g. to simulate the LP tokens
+//! unbonded by the staking contract before the withdraw reply.
+
+mod builders;
+mod ledger;
+mod querier;
+
+pub use builders::{
+    pair_info, ProxyInstantiateMsgBuilder, GENERATOR, LP_TOKEN, PAIR, REWARD_CONTRACT, REWARD_TOKEN,
+};
+pub use ledger::{AssetInfo, Ledger, PairInfo, PairType, PoolAsset, StakerPosition, StakingConfig};
+pub use querier::{mock_dependencies, WasmMockQuerier};
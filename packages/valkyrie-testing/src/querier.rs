@@ -0,0 +1,191 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Binary, Coin, ContractResult, Decimal, Empty, OwnedDeps,
+    Querier, QuerierResult, QueryRequest, StdError, StdResult, SystemError, SystemResult, Uint128,
+    WasmQuery,
+};
+use cw20::{BalanceResponse, Cw20QueryMsg};
+
+use ap_valkyrie::lp_staking::{QueryMsg as StakingQueryMsg, StakerInfoResponse};
+
+use crate::builders::REWARD_CONTRACT;
+use crate::ledger::{Ledger, PoolAsset, StakerPosition, StakingConfig};
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
+
+    OwnedDeps {
+        api: MockApi::default(),
+        storage: MockStorage::default(),
+        querier: custom_querier,
+        custom_query_type: Default::default(),
+    }
+}
+
+/// Full upstream config, the proxy only reads a subset of it
+#[cw_serde]
+struct StakingConfigResponse {
+    token: String,
+    pair: String,
+    lp_token: String,
+    whitelisted_contracts: Vec<String>,
+    distribution_schedule: Vec<(u64, u64, Uint128)>,
+}
+
+#[cw_serde]
+enum PairQueryMsg {
+    Pair {},
+    Pool {},
+}
+
+#[cw_serde]
+struct PoolResponse {
+    assets: Vec<PoolAsset>,
+    total_share: Uint128,
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    ledger: Ledger,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        // MockQuerier doesn't support Custom, so we ignore it completely here
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<Empty>) -> Self {
+        WasmMockQuerier {
+            base,
+            ledger: Ledger::default(),
+        }
+    }
+
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    /// Gives access to the mocked state so it can be changed between contract calls
+    pub fn ledger_mut(&mut self) -> &mut Ledger {
+        &mut self.ledger
+    }
+
+    /// Replaces every CW20 balance
+    pub fn with_token_balances(&mut self, balances: &[(&String, &[(&String, &Uint128)])]) {
+        self.ledger.set_balances(balances);
+    }
+
+    /// Sets the position of the mock contract in the default staking contract
+    pub fn with_reward_info(&mut self, pending_reward: Uint128, deposit_amount: Uint128) {
+        self.ledger.set_staker(
+            REWARD_CONTRACT,
+            MOCK_CONTRACT_ADDR,
+            StakerPosition {
+                bond_amount: deposit_amount,
+                pending_reward,
+            },
+        );
+    }
+
+    /// Sets the config of the default staking contract
+    pub fn with_staking_config(&mut self, token: &str, pair: &str, lp_token: &str) {
+        self.ledger.set_staking_config(
+            REWARD_CONTRACT,
+            StakingConfig {
+                token: token.to_string(),
+                pair: pair.to_string(),
+                lp_token: lp_token.to_string(),
+            },
+        );
+    }
+
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match request {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                let result = if let Some(config) = self.ledger.staking_configs.get(contract_addr) {
+                    self.handle_staking_query(contract_addr, config, msg)
+                } else if self.ledger.pools.contains_key(contract_addr) {
+                    self.handle_pair_query(contract_addr, msg)
+                } else if self.ledger.balances.contains_key(contract_addr) {
+                    self.handle_token_query(contract_addr, msg)
+                } else {
+                    return SystemResult::Err(SystemError::NoSuchContract {
+                        addr: contract_addr.to_string(),
+                    });
+                };
+
+                match result {
+                    Ok(response) => SystemResult::Ok(ContractResult::Ok(response)),
+                    Err(_) => SystemResult::Err(SystemError::UnsupportedRequest {
+                        kind: format!("{} on {}", String::from_utf8_lossy(msg), contract_addr),
+                    }),
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+
+    fn handle_staking_query(
+        &self,
+        contract_addr: &str,
+        config: &StakingConfig,
+        msg: &Binary,
+    ) -> StdResult<Binary> {
+        match from_binary(msg)? {
+            StakingQueryMsg::Config {} => to_binary(&StakingConfigResponse {
+                token: config.token.clone(),
+                pair: config.pair.clone(),
+                lp_token: config.lp_token.clone(),
+                whitelisted_contracts: vec![],
+                distribution_schedule: vec![],
+            }),
+            StakingQueryMsg::StakerInfo { staker } => {
+                let position = self.ledger.staker(contract_addr, &staker);
+                to_binary(&StakerInfoResponse {
+                    staker,
+                    reward_index: Decimal::zero(),
+                    bond_amount: position.bond_amount,
+                    pending_reward: position.pending_reward,
+                })
+            }
+        }
+    }
+
+    fn handle_pair_query(&self, contract_addr: &str, msg: &Binary) -> StdResult<Binary> {
+        let pool = &self.ledger.pools[contract_addr];
+        match from_binary(msg)? {
+            PairQueryMsg::Pair {} => to_binary(&pool.info),
+            PairQueryMsg::Pool {} => to_binary(&PoolResponse {
+                assets: pool.assets.clone(),
+                total_share: pool.total_share,
+            }),
+        }
+    }
+
+    fn handle_token_query(&self, contract_addr: &str, msg: &Binary) -> StdResult<Binary> {
+        match from_binary(msg)? {
+            Cw20QueryMsg::Balance { address } => to_binary(&BalanceResponse {
+                balance: self.ledger.balance(contract_addr, &address),
+            }),
+            _ => Err(StdError::generic_err("Query not mocked")),
+        }
+    }
+}
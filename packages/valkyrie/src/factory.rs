@@ -0,0 +1,99 @@
+//! Messages of the proxy factory contract, which instantiates generator proxies to Valkyrie and
+//! keeps a registry of the proxy deployed for every LP token.
+
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Binary};
+
+/// This structure describes the parameters used for creating the factory.
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// The code id of the generator proxy to Valkyrie contract
+    pub proxy_code_id: u64,
+    /// The generator contract address used by every proxy
+    pub generator_contract_addr: String,
+    /// The admin of the instantiated proxies, able to migrate them. Proxies are not migratable if unset
+    pub proxy_admin: Option<String>,
+    /// The factory owner, also set as owner of the instantiated proxies. Defaults to the instantiator
+    pub owner: Option<String>,
+}
+
+/// This structure describes the execute messages available in the factory.
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Instantiates a proxy for the LP token and registers it. Can only be called by the owner
+    CreateProxy {
+        /// The Astroport pair contract address
+        pair_addr: String,
+        /// The LP token contract address
+        lp_token_addr: String,
+        /// The Valkyrie LP staking contract address
+        reward_contract_addr: String,
+        /// The reward token contract address
+        reward_token_addr: String,
+        /// The proxy label, defaults to a label derived from the LP token
+        label: Option<String>,
+        /// Instantiates the proxy with `instantiate2` at the address derived from the salt.
+        /// Requires a factory built with the `cosmwasm_1_2` feature
+        #[serde(default)]
+        salt: Option<Binary>,
+    },
+    /// Updates the factory config. Can only be called by the owner
+    UpdateConfig {
+        /// The code id used for new proxies
+        proxy_code_id: Option<u64>,
+        /// The admin of new proxies
+        proxy_admin: Option<String>,
+    },
+}
+
+/// This structure describes the query messages available in the factory.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the factory config
+    #[returns(ConfigResponse)]
+    Config {},
+    /// Returns the registered proxies ordered by LP token address
+    #[returns(ProxiesResponse)]
+    Proxies {
+        /// The LP token address to start after
+        start_after: Option<String>,
+        /// Maximum number of proxies returned
+        limit: Option<u32>,
+    },
+    /// Returns the proxy registered for the LP token
+    #[returns(ProxyInfo)]
+    ProxyFor { lp_token: String },
+}
+
+/// This structure describes a migration message of the factory.
+#[cw_serde]
+pub struct MigrateMsg {}
+
+/// This structure describes the response of the [`QueryMsg::Config`] query.
+#[cw_serde]
+pub struct ConfigResponse {
+    /// The factory owner
+    pub owner: Addr,
+    /// The code id used for new proxies
+    pub proxy_code_id: u64,
+    /// The generator contract address used by every proxy
+    pub generator_contract_addr: Addr,
+    /// The admin of new proxies
+    pub proxy_admin: Option<Addr>,
+}
+
+/// This structure describes a registered proxy.
+#[cw_serde]
+pub struct ProxyInfo {
+    /// The LP token contract address
+    pub lp_token: Addr,
+    /// The proxy contract address
+    pub proxy: Addr,
+}
+
+/// This structure describes the response of the [`QueryMsg::Proxies`] query.
+#[cw_serde]
+pub struct ProxiesResponse {
+    pub proxies: Vec<ProxyInfo>,
+}
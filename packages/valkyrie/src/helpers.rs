@@ -0,0 +1,230 @@
+use cosmwasm_schema::schemars::JsonSchema;
+use cosmwasm_schema::serde::de::DeserializeOwned;
+use cosmwasm_schema::serde::{Deserialize, Serialize};
+use cosmwasm_std::{to_binary, Addr, CosmosMsg, QuerierWrapper, StdResult, Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+
+use crate::proxy::{
+    ConfigResponse, Cw20HookMsg, DepositResponse, ExecuteMsg, HarvestInfoResponse, HealthResponse,
+    IdleBalanceResponse, PendingTokenResponse, PendingWithdrawalsResponse, ProxyStatus, QueryMsg,
+    QueryMsgV2, RewardConversionResponse, RewardInfoResponse, RewardResponse, RewardSplitResponse,
+    SettingsResponse, StreamStateResponse,
+};
+
+/// ProxyContract is a wrapper around Addr that provides helpers for calling the proxy
+/// from other contracts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(crate = "cosmwasm_schema::serde")]
+#[schemars(crate = "cosmwasm_schema::schemars")]
+pub struct ProxyContract(pub Addr);
+
+impl ProxyContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    /// Returns the message executing the given proxy message
+    pub fn call<T: Into<ExecuteMsg>>(&self, msg: T) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr().into(),
+            msg: to_binary(&msg.into())?,
+            funds: vec![],
+        }
+        .into())
+    }
+
+    /// Returns the message depositing `amount` LP tokens held by the sender in the proxy
+    pub fn deposit_msg(
+        &self,
+        lp_token: impl Into<String>,
+        amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: lp_token.into(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: self.addr().into(),
+                amount,
+                msg: to_binary(&Cw20HookMsg::Deposit {})?,
+            })?,
+            funds: vec![],
+        }
+        .into())
+    }
+
+    pub fn update_rewards_msg(&self) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::UpdateRewards {})
+    }
+
+    pub fn send_rewards_msg(
+        &self,
+        account: impl Into<String>,
+        amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SendRewards {
+            account: account.into(),
+            amount,
+        })
+    }
+
+    pub fn withdraw_msg(
+        &self,
+        account: impl Into<String>,
+        amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::Withdraw {
+            account: account.into(),
+            amount,
+        })
+    }
+
+    pub fn emergency_withdraw_msg(
+        &self,
+        account: impl Into<String>,
+        amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::EmergencyWithdraw {
+            account: account.into(),
+            amount,
+        })
+    }
+
+    pub fn restake_idle_msg(&self) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::RestakeIdle {})
+    }
+
+    pub fn claim_unbonded_msg(&self, account: impl Into<String>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ClaimUnbonded {
+            account: account.into(),
+        })
+    }
+
+    pub fn deprecate_msg(&self) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::Deprecate {})
+    }
+
+    pub fn harvest_msg(&self) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::Harvest {})
+    }
+
+    pub fn claim_split_msg(&self) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ClaimSplit {})
+    }
+
+    fn query<T: DeserializeOwned>(&self, querier: &QuerierWrapper, msg: &QueryMsg) -> StdResult<T> {
+        querier.query_wasm_smart(self.addr(), msg)
+    }
+
+    pub fn query_config(&self, querier: &QuerierWrapper) -> StdResult<ConfigResponse> {
+        self.query(querier, &QueryMsg::Config {})
+    }
+
+    /// Returns the amount of LP tokens deposited in the proxy, bonded in the staking contract or
+    /// held by the proxy
+    pub fn query_deposit(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
+        self.query(querier, &QueryMsg::Deposit {})
+    }
+
+    /// Returns the amount of released reward tokens held by the proxy
+    pub fn query_reward(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
+        self.query(querier, &QueryMsg::Reward {})
+    }
+
+    /// Returns the amount of rewards pending in the staking contract and of claimed rewards not
+    /// released yet
+    pub fn query_pending_token(&self, querier: &QuerierWrapper) -> StdResult<Option<Uint128>> {
+        self.query(querier, &QueryMsg::PendingToken {})
+    }
+
+    /// Returns the distributed token address, the conversion target if rewards are converted
+    pub fn query_reward_info(&self, querier: &QuerierWrapper) -> StdResult<Addr> {
+        self.query(querier, &QueryMsg::RewardInfo {})
+    }
+
+    /// Typed version of [`Self::query_deposit`]
+    pub fn query_deposit_v2(&self, querier: &QuerierWrapper) -> StdResult<DepositResponse> {
+        self.query(querier, &QueryMsg::V2(QueryMsgV2::Deposit {}))
+    }
+
+    /// Typed version of [`Self::query_reward`]
+    pub fn query_reward_v2(&self, querier: &QuerierWrapper) -> StdResult<RewardResponse> {
+        self.query(querier, &QueryMsg::V2(QueryMsgV2::Reward {}))
+    }
+
+    /// Typed version of [`Self::query_pending_token`]
+    pub fn query_pending_token_v2(
+        &self,
+        querier: &QuerierWrapper,
+    ) -> StdResult<PendingTokenResponse> {
+        self.query(querier, &QueryMsg::V2(QueryMsgV2::PendingToken {}))
+    }
+
+    /// Typed version of [`Self::query_reward_info`]
+    pub fn query_reward_info_v2(&self, querier: &QuerierWrapper) -> StdResult<RewardInfoResponse> {
+        self.query(querier, &QueryMsg::V2(QueryMsgV2::RewardInfo {}))
+    }
+
+    pub fn query_health(&self, querier: &QuerierWrapper) -> StdResult<HealthResponse> {
+        self.query(querier, &QueryMsg::Health {})
+    }
+
+    pub fn query_idle_balance(&self, querier: &QuerierWrapper) -> StdResult<IdleBalanceResponse> {
+        self.query(querier, &QueryMsg::IdleBalance {})
+    }
+
+    pub fn query_settings(&self, querier: &QuerierWrapper) -> StdResult<SettingsResponse> {
+        self.query(querier, &QueryMsg::Settings {})
+    }
+
+    pub fn query_status(&self, querier: &QuerierWrapper) -> StdResult<ProxyStatus> {
+        self.query(querier, &QueryMsg::Status {})
+    }
+
+    pub fn query_harvest_info(&self, querier: &QuerierWrapper) -> StdResult<HarvestInfoResponse> {
+        self.query(querier, &QueryMsg::HarvestInfo {})
+    }
+
+    pub fn query_stream_state(&self, querier: &QuerierWrapper) -> StdResult<StreamStateResponse> {
+        self.query(querier, &QueryMsg::StreamState {})
+    }
+
+    pub fn query_reward_conversion(
+        &self,
+        querier: &QuerierWrapper,
+    ) -> StdResult<Option<RewardConversionResponse>> {
+        self.query(querier, &QueryMsg::RewardConversion {})
+    }
+
+    pub fn query_reward_splits(
+        &self,
+        querier: &QuerierWrapper,
+    ) -> StdResult<Vec<RewardSplitResponse>> {
+        self.query(querier, &QueryMsg::RewardSplits {})
+    }
+
+    /// Returns the rewards accrued to a beneficiary and not claimed yet
+    pub fn query_accrued_split(
+        &self,
+        querier: &QuerierWrapper,
+        recipient: impl Into<String>,
+    ) -> StdResult<Uint128> {
+        self.query(
+            querier,
+            &QueryMsg::AccruedSplit {
+                recipient: recipient.into(),
+            },
+        )
+    }
+
+    pub fn query_pending_withdrawals(
+        &self,
+        querier: &QuerierWrapper,
+        account: impl Into<String>,
+    ) -> StdResult<PendingWithdrawalsResponse> {
+        self.query(
+            querier,
+            &QueryMsg::PendingWithdrawals {
+                account: account.into(),
+            },
+        )
+    }
+}
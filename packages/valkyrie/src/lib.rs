@@ -1,6 +1,18 @@
-use cosmwasm_schema::cw_serde;
+//! Interface of the Astroport generator proxy to Valkyrie.
+//!
+//! [`proxy`] describes the messages of the proxy contract itself, its generator-compatible
+//! variants keep the exact wire format of the Astroport generator proxy interface.
+//! [`lp_staking`] describes the subset of the Valkyrie LP staking contract interface used by the
+//! proxy, together with helpers building its messages.
+//! [`ProxyContract`] builds the proxy messages and queries for contracts calling the proxy.
+//! [`factory`] describes the messages of the proxy factory contract.
+//! [`router`] describes the subset of the Astroport router interface used to convert rewards.
 
-/// This structure describes a migration message.
-/// We currently take no arguments for migrations
-#[cw_serde]
-pub struct MigrateMsg {}
+pub mod factory;
+mod helpers;
+pub mod lp_staking;
+pub mod proxy;
+pub mod router;
+
+pub use helpers::ProxyContract;
+pub use proxy::*;
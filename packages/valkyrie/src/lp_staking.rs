@@ -0,0 +1,126 @@
+//! Wire format of the Valkyrie LP staking contract messages used by the proxy.
+//!
+//! Valkyrie LP staking contract implementation:
+//! https://github.com/valkyrieprotocol/contracts/tree/main/contracts/lp_staking
+
+use cosmwasm_schema::schemars::JsonSchema;
+use cosmwasm_schema::serde::{Deserialize, Serialize};
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{to_binary, CosmosMsg, Decimal, QuerierWrapper, StdResult, Uint128, WasmMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+
+/// This structure describes the execute messages of the Valkyrie LP staking contract.
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Receives a message of type [`Cw20ReceiveMsg`]
+    Receive(Cw20ReceiveMsg),
+    /// Unbonds LP tokens and sends them back to the staker
+    Unbond { amount: Uint128 },
+    /// Sends the pending rewards to the staker
+    Withdraw {},
+}
+
+/// This structure describes the CW20 hook messages of the Valkyrie LP staking contract.
+#[cw_serde]
+pub enum Cw20HookMsg {
+    /// Bonds the received LP tokens on behalf of the sender
+    Bond {},
+}
+
+/// This structure describes the query messages of the Valkyrie LP staking contract.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the staking contract config
+    #[returns(ConfigResponse)]
+    Config {},
+    /// Returns the position of a staker
+    #[returns(StakerInfoResponse)]
+    StakerInfo { staker: String },
+}
+
+/// This structure describes the fields of the Valkyrie LP staking config used by the proxy.
+/// Unknown fields are ignored so the response keeps deserializing as the upstream config grows.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(crate = "cosmwasm_schema::serde")]
+#[schemars(crate = "cosmwasm_schema::schemars")]
+pub struct ConfigResponse {
+    /// The reward token contract address
+    pub token: String,
+    /// The Astroport pair contract address
+    pub pair: String,
+    /// The LP token contract address
+    pub lp_token: String,
+}
+
+/// This structure describes the position of a staker in the Valkyrie LP staking contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(crate = "cosmwasm_schema::serde")]
+#[schemars(crate = "cosmwasm_schema::schemars")]
+pub struct StakerInfoResponse {
+    /// The staker address
+    pub staker: String,
+    /// The reward index of the staker
+    pub reward_index: Decimal,
+    /// Amount of LP tokens bonded by the staker
+    pub bond_amount: Uint128,
+    /// Amount of rewards pending for the staker
+    pub pending_reward: Uint128,
+}
+
+/// Returns the message bonding `amount` LP tokens held by the sender in the staking contract
+pub fn bond_msg(
+    lp_token: impl Into<String>,
+    staking_contract: impl Into<String>,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: lp_token.into(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: staking_contract.into(),
+            amount,
+            msg: to_binary(&Cw20HookMsg::Bond {})?,
+        })?,
+    }))
+}
+
+/// Returns the message unbonding `amount` LP tokens of the sender from the staking contract
+pub fn unbond_msg(staking_contract: impl Into<String>, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: staking_contract.into(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::Unbond { amount })?,
+    }))
+}
+
+/// Returns the message claiming the pending rewards of the sender from the staking contract
+pub fn withdraw_msg(staking_contract: impl Into<String>) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: staking_contract.into(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::Withdraw {})?,
+    }))
+}
+
+/// Returns the config of the staking contract
+pub fn query_config(
+    querier: &QuerierWrapper,
+    staking_contract: impl Into<String>,
+) -> StdResult<ConfigResponse> {
+    querier.query_wasm_smart(staking_contract, &QueryMsg::Config {})
+}
+
+/// Returns the position of the staker in the staking contract
+pub fn query_staker_info(
+    querier: &QuerierWrapper,
+    staking_contract: impl Into<String>,
+    staker: impl Into<String>,
+) -> StdResult<StakerInfoResponse> {
+    querier.query_wasm_smart(
+        staking_contract,
+        &QueryMsg::StakerInfo {
+            staker: staker.into(),
+        },
+    )
+}
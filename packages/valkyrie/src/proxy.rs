@@ -0,0 +1,455 @@
+use cosmwasm_schema::schemars::gen::SchemaGenerator;
+use cosmwasm_schema::schemars::schema::{Schema, SchemaObject, SubschemaValidation};
+use cosmwasm_schema::schemars::JsonSchema;
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+
+use crate::router::{AssetInfo, SwapOperation};
+
+/// This structure describes the parameters used for creating a contract.
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// The generator contract address
+    pub generator_contract_addr: String,
+    /// The Astroport pair contract address
+    pub pair_addr: String,
+    /// The LP token contract address
+    pub lp_token_addr: String,
+    /// The Valkyrie LP staking contract address
+    pub reward_contract_addr: String,
+    /// The reward token contract address
+    pub reward_token_addr: String,
+    /// The contract owner, defaults to the instantiator
+    pub owner: Option<String>,
+}
+
+/// This structure describes the execute messages available in the contract.
+/// The first variants are kept wire-compatible with the Astroport generator proxy interface.
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Receives a message of type [`Cw20ReceiveMsg`]
+    Receive(Cw20ReceiveMsg),
+    /// Claims pending rewards from the Valkyrie LP staking contract
+    UpdateRewards {},
+    /// Sends rewards to a recipient
+    SendRewards { account: String, amount: Uint128 },
+    /// Withdraws LP tokens and sends them to a recipient
+    Withdraw { account: String, amount: Uint128 },
+    /// Withdraws LP tokens without caring about rewards
+    EmergencyWithdraw { account: String, amount: Uint128 },
+    /// Bonds LP tokens held by the proxy into the Valkyrie LP staking contract.
+    /// Can only be called by the owner or the generator
+    RestakeIdle {},
+    /// Updates the optional proxy behaviour. Can only be called by the owner
+    UpdateSettings {
+        /// Whether rewards are claimed before LP tokens are unbonded on withdraw
+        claim_rewards_on_withdraw: Option<bool>,
+        /// Seconds withdrawn LP tokens are queued for, zero pays them out instantly
+        unbonding_period: Option<u64>,
+        /// Whether deposited LP tokens are held by the proxy when bonding fails instead of
+        /// reverting the deposit
+        hold_lp_on_bond_failure: Option<bool>,
+        /// Minimum number of seconds between two harvests
+        harvest_interval: Option<u64>,
+        /// Share of the harvested rewards paid to the caller of Harvest
+        harvest_bounty: Option<Decimal>,
+        /// Pending rewards below this amount are not claimed by UpdateRewards
+        min_claim_amount: Option<Uint128>,
+        /// Seconds claimed rewards are released over, zero releases them at once
+        reward_stream_period: Option<u64>,
+    },
+    /// Transfers the LP tokens of every matured queued withdrawal to the account
+    ClaimUnbonded { account: String },
+    /// Moves the whole bonded position and pending rewards to a new Valkyrie LP staking
+    /// contract. Can only be called by the owner
+    MigrateStakingContract { new_reward_contract: String },
+    /// Winds the proxy down: unbonds all LP tokens into the proxy, claims the final rewards and
+    /// disables deposits. Withdrawals are then paid from the proxy LP balance.
+    /// Can only be called by the owner
+    Deprecate {},
+    /// Claims pending rewards from the Valkyrie LP staking contract and pays the caller a bounty
+    /// out of them. Can be called by anyone once per harvest interval
+    Harvest {},
+    /// Sets or disables the conversion of claimed rewards. Can only be called by the owner, the
+    /// converted token can only change while the proxy holds no deposits
+    UpdateRewardConversion {
+        conversion: Option<RewardConversionInfo>,
+    },
+    /// Sets the beneficiaries claimed rewards are split among, an empty list disables the split.
+    /// The weights must sum to one, the share of the generator is distributed as before.
+    /// Can only be called by the owner
+    UpdateRewardSplits { splits: Vec<RewardSplitInfo> },
+    /// Transfers the rewards accrued to the calling beneficiary
+    ClaimSplit {},
+}
+
+/// This structure describes the conversion of claimed rewards through the Astroport router.
+#[cw_serde]
+pub struct RewardConversionInfo {
+    /// The Astroport router contract address
+    pub router: String,
+    /// The assets the reward token is swapped through in order, the last one is the CW20 token
+    /// distributed instead of the reward token
+    pub route: Vec<AssetInfo>,
+    /// Maximum spread of every swap of the route
+    pub max_spread: Decimal,
+    /// Expected amount of the distributed token per reward token over the whole route. The swap
+    /// fails when it returns less than this price minus the max spread. Without it the spread is
+    /// measured against the current pool prices only
+    pub belief_price: Option<Decimal>,
+}
+
+/// This structure describes a beneficiary of the claimed rewards.
+#[cw_serde]
+pub struct RewardSplitInfo {
+    /// The beneficiary address, the generator address keeps its share for distribution
+    pub recipient: String,
+    /// Share of the claimed rewards accrued to the beneficiary
+    pub weight: Decimal,
+}
+
+/// This structure describes the CW20 hook messages of the contract.
+#[cw_serde]
+pub enum Cw20HookMsg {
+    /// Bonds the received LP tokens in the Valkyrie LP staking contract
+    Deposit {},
+}
+
+/// This structure describes a migration message.
+#[cw_serde]
+pub struct MigrateMsg {
+    /// The contract owner, required when migrating from a version without an owner
+    pub owner: Option<String>,
+}
+
+/// This structure describes the query messages available in the contract.
+/// The first variants are kept wire-compatible with the Astroport generator proxy interface.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the contract config
+    #[returns(ConfigResponse)]
+    Config {},
+    /// Returns the amount of LP tokens deposited in the proxy, bonded in the Valkyrie LP staking
+    /// contract or held by the proxy
+    #[returns(Uint128)]
+    Deposit {},
+    /// Returns the amount of released reward tokens held by the proxy
+    #[returns(Uint128)]
+    Reward {},
+    /// Returns the amount of pending rewards in the Valkyrie LP staking contract and of claimed
+    /// rewards not released yet
+    #[returns(Option<Uint128>)]
+    PendingToken {},
+    /// Returns the address of the distributed token, the conversion target if rewards are converted
+    #[returns(Addr)]
+    RewardInfo {},
+    /// Compares the stored config with the config of the Valkyrie LP staking contract
+    #[returns(HealthCheckResponse)]
+    HealthCheck {},
+    /// Summarises the proxy position and reports failed invariants
+    #[returns(HealthResponse)]
+    Health {},
+    /// Returns the amount of LP tokens held by the proxy that can be restaked
+    #[returns(IdleBalanceResponse)]
+    IdleBalance {},
+    /// Returns the optional proxy behaviour
+    #[returns(SettingsResponse)]
+    Settings {},
+    /// Returns the queued withdrawals of an account
+    #[returns(PendingWithdrawalsResponse)]
+    PendingWithdrawals { account: String },
+    /// Typed versions of the generator queries
+    #[returns(QueryMsgV2Responses)]
+    V2(QueryMsgV2),
+    /// Returns whether the proxy is active or deprecated
+    #[returns(ProxyStatus)]
+    Status {},
+    /// Returns when the proxy was last harvested and when it can be harvested again
+    #[returns(HarvestInfoResponse)]
+    HarvestInfo {},
+    /// Returns the claimed rewards still being released
+    #[returns(StreamStateResponse)]
+    StreamState {},
+    /// Returns the conversion of claimed rewards, if enabled
+    #[returns(Option<RewardConversionResponse>)]
+    RewardConversion {},
+    /// Returns the beneficiaries claimed rewards are split among and their accrued rewards
+    #[returns(Vec<RewardSplitResponse>)]
+    RewardSplits {},
+    /// Returns the rewards accrued to a beneficiary and not claimed yet
+    #[returns(Uint128)]
+    AccruedSplit { recipient: String },
+}
+
+/// This structure describes the typed versions of the generator queries,
+/// available under the `v2` namespace of [`QueryMsg`].
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsgV2 {
+    /// Returns the amount of LP tokens deposited in the proxy, bonded in the Valkyrie LP staking
+    /// contract or held by the proxy
+    #[returns(DepositResponse)]
+    Deposit {},
+    /// Returns the amount of released reward tokens held by the proxy
+    #[returns(RewardResponse)]
+    Reward {},
+    /// Returns the amount of pending rewards in the Valkyrie LP staking contract and of claimed
+    /// rewards not released yet
+    #[returns(PendingTokenResponse)]
+    PendingToken {},
+    /// Returns the distributed token and the contract distributing the rewards
+    #[returns(RewardInfoResponse)]
+    RewardInfo {},
+}
+
+/// This structure describes the response of the [`QueryMsgV2::Deposit`] query.
+#[cw_serde]
+pub struct DepositResponse {
+    /// Amount of LP tokens deposited, bonded or held by the proxy
+    pub amount: Uint128,
+    /// The LP token contract address
+    pub lp_token: Addr,
+}
+
+/// This structure describes the response of the [`QueryMsgV2::Reward`] query.
+#[cw_serde]
+pub struct RewardResponse {
+    /// Amount of released reward tokens held by the proxy
+    pub amount: Uint128,
+    /// The reward token contract address
+    pub token: Addr,
+}
+
+/// This structure describes the response of the [`QueryMsgV2::PendingToken`] query.
+#[cw_serde]
+pub struct PendingTokenResponse {
+    /// Amount of pending rewards
+    pub amount: Uint128,
+    /// The reward token contract address
+    pub token: Addr,
+}
+
+/// This structure describes the response of the [`QueryMsgV2::RewardInfo`] query.
+#[cw_serde]
+pub struct RewardInfoResponse {
+    /// The distributed token contract address, the conversion target if rewards are converted
+    pub token: Addr,
+    /// The Valkyrie LP staking contract address distributing the rewards
+    pub reward_contract: Addr,
+}
+
+/// This structure describes the schema of the [`QueryMsg::V2`] responses, one of the responses
+/// of [`QueryMsgV2`]. It only exists in the schema, every query is answered with the response
+/// type of its [`QueryMsgV2`] variant.
+pub struct QueryMsgV2Responses;
+
+impl JsonSchema for QueryMsgV2Responses {
+    fn schema_name() -> String {
+        "QueryMsgV2Responses".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                one_of: Some(vec![
+                    gen.subschema_for::<DepositResponse>(),
+                    gen.subschema_for::<RewardResponse>(),
+                    gen.subschema_for::<PendingTokenResponse>(),
+                    gen.subschema_for::<RewardInfoResponse>(),
+                ]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+/// This structure describes the response of the [`QueryMsg::Config`] query.
+#[cw_serde]
+pub struct ConfigResponse {
+    /// The generator contract address
+    pub generator_contract_addr: String,
+    /// The Astroport pair contract address
+    pub pair_addr: String,
+    /// The LP token contract address
+    pub lp_token_addr: String,
+    /// The Valkyrie LP staking contract address
+    pub reward_contract_addr: String,
+    /// The reward token contract address
+    pub reward_token_addr: String,
+    /// The contract owner
+    pub owner: String,
+}
+
+/// This structure describes a single mismatch between the proxy config and upstream state.
+#[cw_serde]
+pub struct ConfigDivergence {
+    /// Name of the config field that diverged
+    pub field: String,
+    /// Value stored in the proxy config
+    pub local: String,
+    /// Value reported by the Valkyrie LP staking contract
+    pub upstream: String,
+}
+
+/// This structure describes the response of the [`QueryMsg::HealthCheck`] query.
+#[cw_serde]
+pub struct HealthCheckResponse {
+    /// True if the proxy config matches the Valkyrie LP staking contract config
+    pub healthy: bool,
+    /// The list of config fields that diverged from upstream
+    pub divergences: Vec<ConfigDivergence>,
+}
+
+/// The amount bonded upstream differs from the deposits and the surplus tracked by the proxy
+pub const INVARIANT_BOND_AMOUNT: &str = "bond_amount";
+/// The proxy holds deposited LP tokens that are not bonded upstream
+pub const INVARIANT_NO_IDLE_LP: &str = "no_idle_lp";
+/// The proxy config diverged from the Valkyrie LP staking contract config
+pub const INVARIANT_UPSTREAM_CONFIG: &str = "upstream_config";
+
+/// This structure describes the response of the [`QueryMsg::Health`] query.
+#[cw_serde]
+pub struct HealthResponse {
+    /// Amount of LP tokens bonded according to the Valkyrie LP staking contract
+    pub upstream_bond_amount: Uint128,
+    /// Amount of LP tokens bonded according to the proxy accounting
+    pub local_bond_amount: Uint128,
+    /// Amount of LP tokens bonded as surplus, not part of any deposit
+    pub surplus_bond_amount: Uint128,
+    /// Amount of LP tokens held by the proxy itself and not reserved for withdrawals
+    pub idle_lp_balance: Uint128,
+    /// Part of the idle LP tokens sent to the proxy outside of the deposit flow
+    pub surplus_lp_balance: Uint128,
+    /// Amount of reward tokens held by the proxy
+    pub reward_balance: Uint128,
+    /// Amount of rewards pending in the Valkyrie LP staking contract
+    pub pending_reward: Uint128,
+    /// Contract name stored by cw2
+    pub contract_name: String,
+    /// Contract version stored by cw2
+    pub contract_version: String,
+    /// Whether the proxy is active or deprecated
+    pub status: ProxyStatus,
+    /// Whether deposits, restaking and staking migrations are rejected
+    pub deposits_paused: bool,
+    /// Whether rewards are no longer claimed from the Valkyrie LP staking contract
+    pub reward_claims_paused: bool,
+    /// The list of invariants that do not hold
+    pub failed_invariants: Vec<String>,
+}
+
+/// This structure describes the response of the [`QueryMsg::IdleBalance`] query.
+#[cw_serde]
+pub struct IdleBalanceResponse {
+    /// Amount of LP tokens held by the proxy
+    pub lp_balance: Uint128,
+    /// Amount of LP tokens reserved for queued withdrawals and, once deprecated, for withdrawals
+    pub reserved_amount: Uint128,
+    /// Amount of LP tokens not reserved for withdrawals
+    pub idle_amount: Uint128,
+    /// Amount of deposited LP tokens held after the staking contract rejected the bond
+    pub held_amount: Uint128,
+    /// Amount of idle LP tokens sent to the proxy outside of the deposit flow, e.g. donations or
+    /// an unbond exceeding the withdrawal. Restaked as surplus which is not part of any deposit
+    pub surplus_amount: Uint128,
+}
+
+/// This structure describes the lifecycle of the proxy.
+#[cw_serde]
+pub enum ProxyStatus {
+    /// LP tokens are bonded in the Valkyrie LP staking contract
+    Active,
+    /// LP tokens are held by the proxy and deposits are disabled
+    Deprecated,
+}
+
+/// This structure describes the response of the [`QueryMsg::Settings`] query.
+#[cw_serde]
+pub struct SettingsResponse {
+    /// Whether rewards are claimed before LP tokens are unbonded on withdraw
+    pub claim_rewards_on_withdraw: bool,
+    /// Seconds withdrawn LP tokens are queued for, zero pays them out instantly
+    pub unbonding_period: u64,
+    /// Whether deposited LP tokens are held by the proxy when bonding fails
+    pub hold_lp_on_bond_failure: bool,
+    /// Minimum number of seconds between two harvests
+    pub harvest_interval: u64,
+    /// Share of the harvested rewards paid to the caller of Harvest
+    pub harvest_bounty: Decimal,
+    /// Pending rewards below this amount are not claimed by UpdateRewards
+    pub min_claim_amount: Uint128,
+    /// Seconds claimed rewards are released over, zero releases them at once
+    pub reward_stream_period: u64,
+}
+
+/// This structure describes the response of the [`QueryMsg::HarvestInfo`] query.
+#[cw_serde]
+pub struct HarvestInfoResponse {
+    /// Timestamp in seconds of the last harvest, zero if never harvested
+    pub last_harvest: u64,
+    /// Timestamp in seconds after which Harvest can be called
+    pub next_harvest: u64,
+}
+
+/// This structure describes the response of the [`QueryMsg::StreamState`] query.
+#[cw_serde]
+pub struct StreamStateResponse {
+    /// Seconds claimed rewards are released over
+    pub stream_period: u64,
+    /// Timestamp in seconds the current stream started at
+    pub start_time: u64,
+    /// Timestamp in seconds the current stream releases its last rewards at
+    pub end_time: u64,
+    /// Amount of rewards of the current stream not released yet
+    pub locked_amount: Uint128,
+    /// Amount of claimed rewards waiting to be added to the stream by the next reward update
+    pub unscheduled_amount: Uint128,
+}
+
+/// This structure describes the response of the [`QueryMsg::RewardConversion`] query.
+#[cw_serde]
+pub struct RewardConversionResponse {
+    /// The Astroport router contract address
+    pub router: Addr,
+    /// The swaps converting the reward token
+    pub operations: Vec<SwapOperation>,
+    /// The CW20 token distributed instead of the reward token
+    pub target_token: Addr,
+    /// Maximum spread of every swap
+    pub max_spread: Decimal,
+    /// Expected amount of the target token per reward token the minimum received is derived from
+    pub belief_price: Option<Decimal>,
+}
+
+/// This structure describes a beneficiary returned by the [`QueryMsg::RewardSplits`] query.
+#[cw_serde]
+pub struct RewardSplitResponse {
+    /// The beneficiary address
+    pub recipient: Addr,
+    /// Share of the claimed rewards accrued to the beneficiary
+    pub weight: Decimal,
+    /// Amount of the distributed token accrued to the beneficiary and not claimed yet
+    pub accrued_amount: Uint128,
+}
+
+/// This structure describes a withdrawal waiting for the unbonding period to end.
+#[cw_serde]
+pub struct PendingWithdrawal {
+    /// Amount of LP tokens to be paid out
+    pub amount: Uint128,
+    /// Timestamp in seconds after which the LP tokens can be claimed
+    pub release_time: u64,
+}
+
+/// This structure describes the response of the [`QueryMsg::PendingWithdrawals`] query.
+#[cw_serde]
+pub struct PendingWithdrawalsResponse {
+    /// The queued withdrawals of the account
+    pub withdrawals: Vec<PendingWithdrawal>,
+    /// Amount of LP tokens that can be claimed now
+    pub claimable_amount: Uint128,
+}
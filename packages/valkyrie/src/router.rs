@@ -0,0 +1,120 @@
+//! Wire format of the Astroport router messages used by the proxy to convert rewards.
+//!
+//! Astroport router contract implementation:
+//! https://github.com/astroport-fi/astroport-core/tree/main/contracts/router
+
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{
+    to_binary, Addr, CosmosMsg, Decimal, QuerierWrapper, StdResult, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+
+/// This enum describes an asset of an Astroport pair.
+#[cw_serde]
+pub enum AssetInfo {
+    /// A CW20 token
+    Token { contract_addr: Addr },
+    /// A native denom
+    NativeToken { denom: String },
+}
+
+/// This enum describes a swap through an Astroport pair.
+#[cw_serde]
+pub enum SwapOperation {
+    /// Swaps the offer asset for the ask asset in the Astroport pair of both assets
+    AstroSwap {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+    },
+}
+
+/// This structure describes the CW20 hook messages of the Astroport router.
+#[cw_serde]
+pub enum Cw20HookMsg {
+    /// Swaps the received tokens through the operations
+    ExecuteSwapOperations {
+        operations: Vec<SwapOperation>,
+        /// Minimum amount of the last ask asset received
+        minimum_receive: Option<Uint128>,
+        /// The recipient of the last ask asset, defaults to the sender
+        to: Option<String>,
+        /// Maximum spread of every swap
+        max_spread: Option<Decimal>,
+    },
+}
+
+/// This structure describes the query messages of the Astroport router.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the amount of the last ask asset received for the offer amount
+    #[returns(SimulateSwapOperationsResponse)]
+    SimulateSwapOperations {
+        offer_amount: Uint128,
+        operations: Vec<SwapOperation>,
+    },
+}
+
+/// This structure describes the response of the [`QueryMsg::SimulateSwapOperations`] query.
+#[cw_serde]
+pub struct SimulateSwapOperationsResponse {
+    pub amount: Uint128,
+}
+
+/// Returns the swap operations converting the offer token through the route, every asset of the
+/// route being the ask asset of the previous one
+pub fn route_operations(offer_token: &Addr, route: &[AssetInfo]) -> Vec<SwapOperation> {
+    let offer_asset_info = AssetInfo::Token {
+        contract_addr: offer_token.clone(),
+    };
+    std::iter::once(&offer_asset_info)
+        .chain(route)
+        .zip(route)
+        .map(|(offer, ask)| SwapOperation::AstroSwap {
+            offer_asset_info: offer.clone(),
+            ask_asset_info: ask.clone(),
+        })
+        .collect()
+}
+
+/// Returns the message swapping `amount` tokens of the sender through the router operations
+pub fn swap_msg(
+    offer_token: impl Into<String>,
+    router: impl Into<String>,
+    amount: Uint128,
+    operations: Vec<SwapOperation>,
+    minimum_receive: Option<Uint128>,
+    max_spread: Decimal,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: offer_token.into(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: router.into(),
+            amount,
+            msg: to_binary(&Cw20HookMsg::ExecuteSwapOperations {
+                operations,
+                minimum_receive,
+                to: None,
+                max_spread: Some(max_spread),
+            })?,
+        })?,
+    }))
+}
+
+/// Returns the amount of the last ask asset the router operations return for the offer amount
+pub fn query_simulate_swap(
+    querier: &QuerierWrapper,
+    router: impl Into<String>,
+    offer_amount: Uint128,
+    operations: Vec<SwapOperation>,
+) -> StdResult<Uint128> {
+    let res: SimulateSwapOperationsResponse = querier.query_wasm_smart(
+        router,
+        &QueryMsg::SimulateSwapOperations {
+            offer_amount,
+            operations,
+        },
+    )?;
+    Ok(res.amount)
+}
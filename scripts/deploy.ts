@@ -29,6 +29,7 @@ async function uploadAndInitGenProxyToVkr(terra: LCDClient, wallet: Wallet) {
     console.log('Deploy the Generator proxy to vkr...');
 
     chainConfigs.proxyVKR.admin ||= chainConfigs.generalInfo.multisig
+    chainConfigs.proxyVKR.initMsg.owner ||= chainConfigs.generalInfo.multisig
 
     network.generatorProxyToVkrAddress = await deployContract(
       terra,
@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+
+use ap_valkyrie::{InstantiateMsg, MigrateMsg};
+use bech32::FromBase32;
+use cosmwasm_std::{Coin, Uint64};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// This structure describes a chain config from `scripts/chain_configs`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainConfig {
+    pub general_info: GeneralInfo,
+    #[serde(rename = "proxyVKR")]
+    pub proxy_vkr: ProxyConfig,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct GeneralInfo {
+    /// The multisig owning the deployed contracts
+    pub multisig: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyConfig {
+    /// The contract admin, defaults to the multisig
+    pub admin: Option<String>,
+    pub init_msg: InitMsgConfig,
+    pub label: String,
+}
+
+/// The proxy instantiate message as written in the chain config, every address may be unset.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct InitMsgConfig {
+    pub generator_contract_addr: Option<String>,
+    pub pair_addr: Option<String>,
+    pub lp_token_addr: Option<String>,
+    pub reward_contract_addr: Option<String>,
+    pub reward_token_addr: Option<String>,
+    /// The proxy owner, defaults to the multisig
+    pub owner: Option<String>,
+}
+
+/// This structure describes the `MsgInstantiateContract` deploying the proxy, as signed with the
+/// wasm module of the chain.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MsgInstantiateContract {
+    #[serde(rename = "@type")]
+    pub type_url: String,
+    /// The multisig signing the deployment
+    pub sender: String,
+    pub admin: String,
+    pub code_id: Uint64,
+    pub label: String,
+    pub msg: InstantiateMsg,
+    pub funds: Vec<Coin>,
+}
+
+/// This structure describes the `MsgMigrateContract` migrating a deployed proxy, as signed with the
+/// wasm module of the chain.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MsgMigrateContract {
+    #[serde(rename = "@type")]
+    pub type_url: String,
+    /// The contract admin signing the migration
+    pub sender: String,
+    pub contract: String,
+    pub code_id: Uint64,
+    pub msg: MigrateMsg,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ConfigError {
+    #[error("{field} is not set")]
+    MissingField { field: String },
+
+    #[error("{field}: {address} is not a valid bech32 address")]
+    InvalidAddress { field: String, address: String },
+
+    #[error("{field}: {address} has prefix {actual}, expected {expected}")]
+    WrongPrefix {
+        field: String,
+        address: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("{first} and {second} are both set to {address}")]
+    DuplicateAddress {
+        first: String,
+        second: String,
+        address: String,
+    },
+}
+
+impl ChainConfig {
+    /// Applies the defaults used by `scripts/deploy.ts` and validates every address of the proxy
+    /// deployment against the given bech32 prefix. Contract addresses must be distinct.
+    /// Returns the message instantiating the given code, sent by the multisig
+    pub fn deployment(
+        &self,
+        prefix: &str,
+        code_id: u64,
+    ) -> Result<MsgInstantiateContract, ConfigError> {
+        let multisig = required("generalInfo.multisig", &self.general_info.multisig, prefix)?;
+        let init_msg = &self.proxy_vkr.init_msg;
+
+        let generator = required(
+            "generator_contract_addr",
+            &init_msg.generator_contract_addr,
+            prefix,
+        )?;
+        let pair = required("pair_addr", &init_msg.pair_addr, prefix)?;
+        let lp_token = required("lp_token_addr", &init_msg.lp_token_addr, prefix)?;
+        let reward_contract = required(
+            "reward_contract_addr",
+            &init_msg.reward_contract_addr,
+            prefix,
+        )?;
+        let reward_token = required("reward_token_addr", &init_msg.reward_token_addr, prefix)?;
+
+        let mut seen: HashMap<&str, &str> = HashMap::new();
+        for (field, address) in [
+            ("generator_contract_addr", &generator),
+            ("pair_addr", &pair),
+            ("lp_token_addr", &lp_token),
+            ("reward_contract_addr", &reward_contract),
+            ("reward_token_addr", &reward_token),
+        ] {
+            if let Some(first) = seen.insert(address, field) {
+                return Err(ConfigError::DuplicateAddress {
+                    first: first.to_string(),
+                    second: field.to_string(),
+                    address: address.clone(),
+                });
+            }
+        }
+
+        let owner = optional("owner", &init_msg.owner, prefix)?.unwrap_or_else(|| multisig.clone());
+        let admin =
+            optional("admin", &self.proxy_vkr.admin, prefix)?.unwrap_or_else(|| multisig.clone());
+
+        Ok(MsgInstantiateContract {
+            type_url: "/cosmwasm.wasm.v1.MsgInstantiateContract".to_string(),
+            sender: multisig,
+            admin,
+            code_id: code_id.into(),
+            label: self.proxy_vkr.label.clone(),
+            msg: InstantiateMsg {
+                generator_contract_addr: generator,
+                pair_addr: pair,
+                lp_token_addr: lp_token,
+                reward_contract_addr: reward_contract,
+                reward_token_addr: reward_token,
+                owner: Some(owner),
+            },
+            funds: vec![],
+        })
+    }
+
+    /// Returns the message migrating the proxy contract to the given code, sent by the contract
+    /// admin. The migrate message sets the owner used on instantiation
+    pub fn migrate_msg(
+        &self,
+        prefix: &str,
+        contract: &str,
+        code_id: u64,
+    ) -> Result<MsgMigrateContract, ConfigError> {
+        let multisig = required("generalInfo.multisig", &self.general_info.multisig, prefix)?;
+        let owner = optional("owner", &self.proxy_vkr.init_msg.owner, prefix)?
+            .unwrap_or_else(|| multisig.clone());
+        let admin = optional("admin", &self.proxy_vkr.admin, prefix)?.unwrap_or(multisig);
+
+        Ok(MsgMigrateContract {
+            type_url: "/cosmwasm.wasm.v1.MsgMigrateContract".to_string(),
+            sender: admin,
+            contract: validate_address("contract", contract, prefix)?,
+            code_id: code_id.into(),
+            msg: MigrateMsg { owner: Some(owner) },
+        })
+    }
+}
+
+fn required(field: &str, address: &Option<String>, prefix: &str) -> Result<String, ConfigError> {
+    optional(field, address, prefix)?.ok_or_else(|| ConfigError::MissingField {
+        field: field.to_string(),
+    })
+}
+
+fn optional(
+    field: &str,
+    address: &Option<String>,
+    prefix: &str,
+) -> Result<Option<String>, ConfigError> {
+    match address.as_deref() {
+        None | Some("") => Ok(None),
+        Some(address) => validate_address(field, address, prefix).map(Some),
+    }
+}
+
+/// Checks that the address is valid bech32 with the expected human readable part
+pub fn validate_address(field: &str, address: &str, prefix: &str) -> Result<String, ConfigError> {
+    let invalid = || ConfigError::InvalidAddress {
+        field: field.to_string(),
+        address: address.to_string(),
+    };
+    let (hrp, data, _) = bech32::decode(address).map_err(|_| invalid())?;
+    if Vec::<u8>::from_base32(&data)
+        .map_err(|_| invalid())?
+        .is_empty()
+    {
+        return Err(invalid());
+    }
+    if hrp != prefix {
+        return Err(ConfigError::WrongPrefix {
+            field: field.to_string(),
+            address: address.to_string(),
+            expected: prefix.to_string(),
+            actual: hrp,
+        });
+    }
+    Ok(address.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTISIG: &str = "terra174gu7kg8ekk5gsxdma5jlfcedm653tyg6ayppw";
+    const GENERATOR: &str = "terra1ksvlfex49desf4c452j6dewdjs6c48nafemetuwjyj6yexd7x3wqvwa7j9";
+    const PAIR: &str = "terra1gc4d4v82vjgkz0ag28lrmlxx3tf6sq69tmaujjpe7jwmnqakkx0qm28j2l";
+    const LP_TOKEN: &str = "terra1x46rqay4d3cssq8gxxvqz8xt6nwlz4td20k38v";
+
+    fn contract(seed: u8) -> String {
+        bech32::encode(
+            "terra",
+            bech32::ToBase32::to_base32(&[seed; 32]),
+            bech32::Variant::Bech32,
+        )
+        .unwrap()
+    }
+
+    fn chain_config() -> ChainConfig {
+        ChainConfig {
+            general_info: GeneralInfo {
+                multisig: Some(MULTISIG.to_string()),
+            },
+            proxy_vkr: ProxyConfig {
+                admin: None,
+                init_msg: InitMsgConfig {
+                    generator_contract_addr: Some(GENERATOR.to_string()),
+                    pair_addr: Some(PAIR.to_string()),
+                    lp_token_addr: Some(LP_TOKEN.to_string()),
+                    reward_contract_addr: Some(contract(1)),
+                    reward_token_addr: Some(contract(2)),
+                    owner: None,
+                },
+                label: "Astroport generator proxy to VKR".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn deployment_defaults_to_multisig() {
+        let deployment = chain_config().deployment("terra", 42).unwrap();
+        assert_eq!(deployment.sender, MULTISIG);
+        assert_eq!(deployment.admin, MULTISIG);
+        assert_eq!(deployment.msg.owner, Some(MULTISIG.to_string()));
+        assert_eq!(deployment.msg.generator_contract_addr, GENERATOR);
+        assert_eq!(deployment.msg.reward_token_addr, contract(2));
+    }
+
+    #[test]
+    fn messages_are_ready_to_sign() {
+        let deployment = chain_config().deployment("terra", 42).unwrap();
+        let json = serde_json::to_value(&deployment).unwrap();
+        assert_eq!(json["@type"], "/cosmwasm.wasm.v1.MsgInstantiateContract");
+        assert_eq!(json["code_id"], "42");
+        assert_eq!(json["msg"]["pair_addr"], PAIR);
+        assert_eq!(json["funds"], serde_json::json!([]));
+
+        let mut config = chain_config();
+        config.proxy_vkr.admin = Some(contract(4));
+        let migration = config.migrate_msg("terra", &contract(3), 43).unwrap();
+        assert_eq!(migration.sender, contract(4));
+        assert_eq!(migration.msg.owner, Some(MULTISIG.to_string()));
+        let json = serde_json::to_value(&migration).unwrap();
+        assert_eq!(json["@type"], "/cosmwasm.wasm.v1.MsgMigrateContract");
+        assert_eq!(json["contract"], contract(3));
+        assert_eq!(json["code_id"], "43");
+
+        assert_eq!(
+            chain_config()
+                .migrate_msg("terra", "terra1notbech32", 43)
+                .unwrap_err(),
+            ConfigError::InvalidAddress {
+                field: "contract".to_string(),
+                address: "terra1notbech32".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn deployment_rejects_invalid_configs() {
+        let mut config = chain_config();
+        config.proxy_vkr.init_msg.pair_addr = None;
+        assert_eq!(
+            config.deployment("terra", 42).unwrap_err(),
+            ConfigError::MissingField {
+                field: "pair_addr".to_string()
+            }
+        );
+
+        let mut config = chain_config();
+        config.proxy_vkr.init_msg.lp_token_addr = Some("terra1notbech32".to_string());
+        assert_eq!(
+            config.deployment("terra", 42).unwrap_err(),
+            ConfigError::InvalidAddress {
+                field: "lp_token_addr".to_string(),
+                address: "terra1notbech32".to_string(),
+            }
+        );
+
+        assert_eq!(
+            chain_config().deployment("juno", 42).unwrap_err(),
+            ConfigError::WrongPrefix {
+                field: "generalInfo.multisig".to_string(),
+                address: MULTISIG.to_string(),
+                expected: "juno".to_string(),
+                actual: "terra".to_string(),
+            }
+        );
+
+        let mut config = chain_config();
+        config.proxy_vkr.init_msg.reward_token_addr = Some(LP_TOKEN.to_string());
+        assert_eq!(
+            config.deployment("terra", 42).unwrap_err(),
+            ConfigError::DuplicateAddress {
+                first: "lp_token_addr".to_string(),
+                second: "reward_token_addr".to_string(),
+                address: LP_TOKEN.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn shipped_configs_parse() {
+        for chain in ["localterra", "phoenix-1", "pisco-1"] {
+            let path = format!(
+                "{}/../../scripts/chain_configs/{}.json",
+                env!("CARGO_MANIFEST_DIR"),
+                chain
+            );
+            let config: ChainConfig =
+                serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+            // the configs are filled in right before a deployment
+            assert!(matches!(
+                config.deployment("terra", 42),
+                Err(ConfigError::MissingField { .. })
+            ));
+            assert!(config.migrate_msg("terra", &contract(3), 42).is_ok());
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Validates the chain configs used by `scripts/deploy.ts` and prints the proxy messages
+//! ready to be signed, without connecting to a chain.
+//!
+//! ```text
+//! proxy-config instantiate <chain_config.json> --code-id <id> [--prefix terra]
+//! proxy-config migrate <chain_config.json> --contract <address> --code-id <id> [--prefix terra]
+//! proxy-config update-settings [--claim-rewards-on-withdraw <bool>] [--unbonding-period <seconds>]
+//!     [--hold-lp-on-bond-failure <bool>] [--harvest-interval <seconds>] [--harvest-bounty <decimal>]
+//!     [--min-claim-amount <amount>] [--reward-stream-period <seconds>]
+//! ```
+
+mod config;
+
+use std::fmt::Display;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use ap_valkyrie::ExecuteMsg;
+use cosmwasm_std::{Decimal, Uint128};
+use serde::Serialize;
+
+use crate::config::ChainConfig;
+
+const DEFAULT_PREFIX: &str = "terra";
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<String, String> {
+    let (command, args) = args.split_first().ok_or_else(usage)?;
+    let mut path = None;
+    let mut prefix = DEFAULT_PREFIX.to_string();
+    let mut code_id = None;
+    let mut contract = None;
+    let mut claim_rewards_on_withdraw = None;
+    let mut unbonding_period = None;
+    let mut hold_lp_on_bond_failure = None;
+    let mut harvest_interval = None;
+    let mut harvest_bounty = None;
+    let mut min_claim_amount = None;
+    let mut reward_stream_period = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} expects a value", arg))
+        };
+        match arg.as_str() {
+            "--prefix" => prefix = value()?,
+            "--code-id" => code_id = Some(parse::<u64>(arg, &value()?)?),
+            "--contract" => contract = Some(value()?),
+            "--claim-rewards-on-withdraw" => {
+                claim_rewards_on_withdraw = Some(parse::<bool>(arg, &value()?)?)
+            }
+            "--unbonding-period" => unbonding_period = Some(parse::<u64>(arg, &value()?)?),
+            "--hold-lp-on-bond-failure" => {
+                hold_lp_on_bond_failure = Some(parse::<bool>(arg, &value()?)?)
+            }
+            "--harvest-interval" => harvest_interval = Some(parse::<u64>(arg, &value()?)?),
+            "--harvest-bounty" => harvest_bounty = Some(parse::<Decimal>(arg, &value()?)?),
+            "--min-claim-amount" => min_claim_amount = Some(parse::<Uint128>(arg, &value()?)?),
+            "--reward-stream-period" => reward_stream_period = Some(parse::<u64>(arg, &value()?)?),
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg.clone()),
+            _ => return Err(format!("unexpected argument {}\n{}", arg, usage())),
+        }
+    }
+
+    match command.as_str() {
+        "instantiate" => {
+            let code_id = code_id.ok_or("instantiate expects --code-id")?;
+            let deployment = read_config(path)?
+                .deployment(&prefix, code_id)
+                .map_err(|err| err.to_string())?;
+            to_json(&deployment)
+        }
+        "migrate" => {
+            let code_id = code_id.ok_or("migrate expects --code-id")?;
+            let contract = contract.ok_or("migrate expects --contract")?;
+            let msg = read_config(path)?
+                .migrate_msg(&prefix, &contract, code_id)
+                .map_err(|err| err.to_string())?;
+            to_json(&msg)
+        }
+        "update-settings" => {
+            if claim_rewards_on_withdraw.is_none()
+                && unbonding_period.is_none()
+                && hold_lp_on_bond_failure.is_none()
+                && harvest_interval.is_none()
+                && harvest_bounty.is_none()
+                && min_claim_amount.is_none()
+                && reward_stream_period.is_none()
+            {
+                return Err("update-settings expects at least one setting".to_string());
+            }
+            to_json(&ExecuteMsg::UpdateSettings {
+                claim_rewards_on_withdraw,
+                unbonding_period,
+                hold_lp_on_bond_failure,
+                harvest_interval,
+                harvest_bounty,
+                min_claim_amount,
+                reward_stream_period,
+            })
+        }
+        _ => Err(usage()),
+    }
+}
+
+fn parse<T: FromStr>(arg: &str, value: &str) -> Result<T, String>
+where
+    T::Err: Display,
+{
+    value
+        .parse()
+        .map_err(|err| format!("{} {}: {}", arg, value, err))
+}
+
+fn read_config(path: Option<String>) -> Result<ChainConfig, String> {
+    let path = path.ok_or_else(usage)?;
+    let content = std::fs::read_to_string(&path).map_err(|err| format!("{}: {}", path, err))?;
+    serde_json::from_str(&content).map_err(|err| format!("{}: {}", path, err))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|err| err.to_string())
+}
+
+fn usage() -> String {
+    [
+        "usage:",
+        "  proxy-config instantiate <chain_config.json> --code-id <id> [--prefix terra]",
+        "  proxy-config migrate <chain_config.json> --contract <address> --code-id <id> [--prefix terra]",
+        "  proxy-config update-settings [--claim-rewards-on-withdraw <bool>] [--unbonding-period <seconds>]",
+        "      [--hold-lp-on-bond-failure <bool>] [--harvest-interval <seconds>] [--harvest-bounty <decimal>]",
+        "      [--min-claim-amount <amount>] [--reward-stream-period <seconds>]",
+    ]
+    .join("\n")
+}